@@ -1,50 +1,478 @@
+use std::collections::HashSet;
+
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::LitStr;
+use syn::{Generics, Ident, LitStr};
 
 
 use super::parse::{
     cargo::Cargo,
+    cron::{Cron, CronJob},
+    derive::{DeriveInstaller, Entry},
     ensure::Ensure,
+    extract::{Archive, ArchiveFormat, Extract},
+    fonts::{FontDownload, Fonts},
+    gitconfig::{GitConfig, GitConfigEntry},
+    header::Header,
     package::{Package, Packages},
+    repositories::Repositories,
+    rustup::Rustup,
     script::Script,
+    secrets::{Secret, Secrets},
+    services::{Services, Unit, UnitScope, UnitState},
+    shell::Snippet,
+    ssh::{Ssh, SshHost, SshKey, SshKeyType},
+    step::Step,
     symlinks::{Symlinks, Symlink},
+    tools::{Go, Npm, Pipx, Tool},
     Installer, Section,
 };
 
 pub fn generate_installer(installer: &Installer) -> TokenStream {
-    let install = installer.sections.iter().map(generate_section);
+    let chunks = generate_chunks(&installer.sections);
+    let waves = waves(&chunks);
+    let wave_count = waves.iter().max().map_or(0, |w| w + 1);
+
+    // the range of `vec` that each chunk pushed is recorded in its wave
+    let install = chunks.iter().zip(&waves).map(|((_, chunk), wave)| quote! {
+        {
+            let start = vec.len();
+            #chunk
+            waves[#wave].push(start..vec.len());
+        }
+    });
+
+    let Header { attrs, vis, name } = &installer.header;
+    let uninstall = generate_uninstall_sections(&installer.sections);
+    let installable = generate_installable(
+        name,
+        &Generics::default(),
+        quote! { &self.sections },
+        quote! {
+            {
+                let mut vec: ::std::vec::Vec<::std::boxed::Box<dyn ::dotinstall::Installable + ::std::marker::Send + ::std::marker::Sync>> = ::std::vec![];
+                #uninstall
+                vec
+            }
+        },
+        generate_parallel(),
+    );
+    let consts = generate_consts(&installer.sections);
+
+    // the font cache is refreshed once, in a wave of its own after every font is in place
+    let font_cache = has_fonts(&installer.sections).then(|| quote! {
+        let start = vec.len();
+        vec.push(::std::boxed::Box::new(::dotinstall::FontCache));
+        waves.push(::std::vec![start..vec.len()]);
+    });
 
     quote! {
-    
-        use ::dotinstall::Installable;
+        #(#attrs)*
+        #vis struct #name {
+            sections: ::std::vec::Vec<::std::boxed::Box<dyn ::dotinstall::Installable + ::std::marker::Send + ::std::marker::Sync>>,
+            waves: ::std::vec::Vec<::std::vec::Vec<::std::ops::Range<usize>>>,
+        }
+
+        #installable
 
-        pub struct Installer {
-            sections: ::std::vec::Vec<::std::boxed::Box<dyn ::dotinstall::Installable>>
+        impl #name {
+            #consts
+
+            pub fn new() -> Self {
+                let mut vec: ::std::vec::Vec<::std::boxed::Box<dyn ::dotinstall::Installable + ::std::marker::Send + ::std::marker::Sync>> = ::std::vec![];
+                let mut waves: ::std::vec::Vec<::std::vec::Vec<::std::ops::Range<usize>>> = ::std::vec![::std::vec![]; #wave_count];
+
+                #(#install)*
+                #font_cache
+
+                Self { sections: vec, waves }
+            }
         }
 
-        impl ::dotinstall::Installable for Installer {
-            fn install(&self, ctx: &::dotinstall::Context) -> ::std::result::Result<(), ::std::boxed::Box<dyn ::std::error::Error>> {
-                for section in &self.sections {
-                    section.install(ctx)?;
+    }
+}
+
+/// Generates code that installs `self.sections` a wave at a time when the context allows it, with each of a wave's chunks on its own thread
+fn generate_parallel() -> TokenStream {
+    quote! {
+        if ctx.parallel() {
+            return ::std::thread::scope(|scope| {
+                for wave in &self.waves {
+                    let handles: ::std::vec::Vec<_> = wave
+                        .iter()
+                        .map(|range| {
+                            let sections = &self.sections[range.clone()];
+                            scope.spawn(move || {
+                                for section in sections {
+                                    // errors aren't `Send`, so only their message leaves the thread
+                                    ::dotinstall::Installable::install(&**section, ctx).map_err(|e| e.to_string())?;
+                                }
+                                ::std::result::Result::Ok::<(), ::std::string::String>(())
+                            })
+                        })
+                        .collect();
+
+                    // the whole wave finishes before the first error is returned
+                    let results: ::std::vec::Vec<_> = handles
+                        .into_iter()
+                        .map(|handle| handle.join().unwrap_or_else(|e| ::std::panic::resume_unwind(e)))
+                        .collect();
+                    for result in results {
+                        result?;
+                    }
                 }
                 ::std::result::Result::Ok(())
+            });
+        }
+    }
+}
+
+pub fn generate_derive(derive: &DeriveInstaller) -> TokenStream {
+    // sections are only merged and reordered up to the next nested installer
+    let mut install = vec![];
+    let mut uninstall = vec![];
+    let mut sections = vec![];
+    for entry in &derive.entries {
+        match entry {
+            Entry::Section(section) => sections.push(section),
+            Entry::Nested(member) => {
+                install.push(generate_sections(sections.iter().copied()));
+                uninstall.push(generate_uninstall_sections(sections.drain(..)));
+                let nested = quote! {
+                    vec.push(::std::boxed::Box::new(&self.#member));
+                };
+                install.push(nested.clone());
+                uninstall.push(nested);
             }
         }
+    }
+    install.push(generate_sections(sections.iter().copied()));
+    uninstall.push(generate_uninstall_sections(sections));
 
-        impl Installer {
-            pub fn new() -> Self {
-                let mut vec: ::std::vec::Vec<::std::boxed::Box<dyn ::dotinstall::Installable>> = ::std::vec![];
+    let sections = derive.entries.iter().filter_map(|entry| match entry {
+        Entry::Section(section) => Some(section),
+        Entry::Nested(_) => None,
+    });
+    if has_fonts(sections) {
+        install.push(quote! {
+            vec.push(::std::boxed::Box::new(::dotinstall::FontCache));
+        });
+    }
 
+    // nested installers can contain anything, so derived installers always install sequentially
+    generate_installable(
+        &derive.name,
+        &derive.generics,
+        quote! {
+            {
+                let mut vec: ::std::vec::Vec<::std::boxed::Box<dyn ::dotinstall::Installable + '_>> = ::std::vec![];
                 #(#install)*
+                vec
+            }
+        },
+        quote! {
+            {
+                let mut vec: ::std::vec::Vec<::std::boxed::Box<dyn ::dotinstall::Installable + '_>> = ::std::vec![];
+                #(#uninstall)*
+                vec
+            }
+        },
+        quote! {},
+    )
+}
+
+/// Generates the `Installable` impl for `name`, where `sections` evaluates to the boxed sections to install, in order, `uninstall` to the
+/// ones `uninstall` undoes, and `parallel` can install them another way before the sequential loop
+fn generate_installable(
+    name: &Ident,
+    generics: &Generics,
+    sections: TokenStream,
+    uninstall: TokenStream,
+    parallel: TokenStream,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ::dotinstall::Installable for #name #ty_generics #where_clause {
+            fn install(&self, ctx: &::dotinstall::Context) -> ::std::result::Result<(), ::std::boxed::Box<dyn ::std::error::Error>> {
+                // the plan goes to the context's reporter, so the caller decides where it ends up
+                if ctx.dry_run() {
+                    ctx.report(::dotinstall::Installable::plan(self, ctx));
+                    return ::std::result::Result::Ok(());
+                }
+
+                #parallel
+
+                let sections = #sections;
+                for section in sections.iter() {
+                    ::dotinstall::Installable::install(&**section, ctx)?;
+                }
+                ::std::result::Result::Ok(())
+            }
+
+            // sections are undone in reverse, so e.g. symlinks are removed before the directories they're in
+            fn uninstall(&self, ctx: &::dotinstall::Context) -> ::std::result::Result<(), ::std::boxed::Box<dyn ::std::error::Error>> {
+                if ctx.dry_run() {
+                    ctx.report(::dotinstall::Installable::plan_uninstall(self, ctx));
+                    return ::std::result::Result::Ok(());
+                }
+
+                let sections = #uninstall;
+                for section in sections.iter().rev() {
+                    ::dotinstall::Installable::uninstall(&**section, ctx)?;
+                }
+                ::std::result::Result::Ok(())
+            }
+
+            fn plan(&self, ctx: &::dotinstall::Context) -> ::dotinstall::Plan {
+                let sections = #sections;
+                let mut plan = ::dotinstall::Plan::default();
+                for section in sections.iter() {
+                    plan.extend(::dotinstall::Installable::plan(&**section, ctx));
+                }
+                plan
+            }
+
+            fn plan_uninstall(&self, ctx: &::dotinstall::Context) -> ::dotinstall::Plan {
+                let sections = #uninstall;
+                let mut plan = ::dotinstall::Plan::default();
+                for section in sections.iter().rev() {
+                    plan.extend(::dotinstall::Installable::plan_uninstall(&**section, ctx));
+                }
+                plan
+            }
+
+            fn check(&self, ctx: &::dotinstall::Context) -> ::std::vec::Vec<::dotinstall::Drift> {
+                let sections = #sections;
+                sections
+                    .iter()
+                    .flat_map(|section| ::dotinstall::Installable::check(&**section, ctx))
+                    .collect()
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Installs only the steps matched by `selector`, which also applies to nested installers
+            pub fn install_only(&self, ctx: &::dotinstall::Context, selector: &::dotinstall::Selector) -> ::std::result::Result<(), ::std::boxed::Box<dyn ::std::error::Error>> {
+                let ctx = ctx.with_selector(selector);
+                ::dotinstall::Installable::install(self, &ctx)
+            }
+        }
+    }
+}
+
+/// Generates code that pushes each of `sections` onto `vec`, reordering sections that others depend on and merging sections that should only run once
+fn generate_sections<'a>(sections: impl IntoIterator<Item = &'a Section>) -> TokenStream {
+    let chunks = generate_chunks(sections).into_iter().map(|(_, chunk)| chunk);
+    quote! {
+        #(#chunks)*
+    }
+}
+
+/// Like `generate_sections`, but keeps the code for each section separate, along with the first section it was generated from
+fn generate_chunks<'a>(sections: impl IntoIterator<Item = &'a Section>) -> Vec<(&'a Section, TokenStream)> {
+    let mut sections: Vec<_> = sections.into_iter().collect();
+    hoist(
+        &mut sections,
+        |s| matches!(s, Section::Repositories(_)),
+        |s| matches!(s, Section::Packages(_)),
+    );
+    hoist(
+        &mut sections,
+        |s| matches!(s, Section::Rustup(_)),
+        |s| matches!(s, Section::Cargo(_)),
+    );
+
+    // `packages` sections are merged into the first of each run, so each package manager runs as few times as possible.
+    // scripts and steps end a run, since they can add the repositories that later packages come from
+    let mut packages_merged = vec![false; sections.len()];
+
+    // and for `aliases` and `functions`, which end up in the same snippet files
+    let mut shell = sections
+        .iter()
+        .copied()
+        .filter(|s| matches!(s, Section::Aliases(_) | Section::Functions(_)));
+    let mut shell_emitted = false;
+
+    sections
+        .iter()
+        .enumerate()
+        .filter_map(|(i, section)| match section {
+            Section::Packages(_) if packages_merged[i] => None,
+            Section::Packages(_) => {
+                let run = merge_run(
+                    &sections[i..],
+                    &mut packages_merged[i..],
+                    |s| matches!(s, Section::Packages(_)),
+                    |s| matches!(s, Section::Script(_) | Section::Step(_)),
+                );
+                let packages = run.into_iter().flat_map(|s| match s {
+                    Section::Packages(Packages { packages, .. }) => packages.iter(),
+                    _ => unreachable!("only `packages` sections are merged"),
+                });
+                Some((*section, generate_packages(packages)))
+            }
+            Section::Aliases(_) | Section::Functions(_) if shell_emitted => None,
+            Section::Aliases(_) | Section::Functions(_) => {
+                shell_emitted = true;
+                Some((*section, generate_shell(shell.by_ref())))
+            }
+            _ => Some((*section, generate_section(section))),
+        })
+        .collect()
+}
+
+/// Returns the sections at the start of `sections` that match `merge`, stopping at the first that matches `barrier`, and marks them as `merged`
+fn merge_run<'a>(
+    sections: &[&'a Section],
+    merged: &mut [bool],
+    merge: fn(&Section) -> bool,
+    barrier: fn(&Section) -> bool,
+) -> Vec<&'a Section> {
+    let mut run = vec![];
+    for (section, merged) in sections.iter().zip(merged) {
+        if barrier(section) {
+            break;
+        }
+        if merge(section) {
+            *merged = true;
+            run.push(*section);
+        }
+    }
+    run
+}
+
+/// What a section touches, which decides what it can run at the same time as
+enum Resources {
+    /// Could touch anything, so nothing runs alongside it
+    Everything,
+    Some(Vec<&'static str>),
+}
 
-                Self { sections: vec }
+/// Sections that share a resource are never run at the same time, and the later one waits for the earlier.
+/// "packages" is the system package manager's lock, and "home-files" is shared by everything that writes files the installer
+/// declares into the home directory, since those files often include each other or live in the same directories
+fn resources(section: &Section) -> Resources {
+    let resources = match section {
+        Section::Packages(_) | Section::Repositories(_) => vec!["packages"],
+        Section::Rustup(_) | Section::Cargo(_) => vec!["rust"],
+        // units can come from files that other sections put in place
+        Section::Services(_) => vec!["services", "home-files"],
+        Section::Aliases(_) | Section::Functions(_) => vec!["shell", "home-files"],
+        Section::Cron(_) => vec!["cron"],
+        Section::Extract(_) => vec!["extract", "home-files"],
+        Section::Fonts(_) => vec!["fonts", "home-files"],
+        Section::GitConfig(_) => vec!["gitconfig", "home-files"],
+        Section::Go(_) => vec!["go"],
+        Section::Npm(_) => vec!["npm"],
+        Section::Pipx(_) => vec!["pipx"],
+        Section::Secrets(_) => vec!["secrets", "home-files"],
+        Section::Ssh(_) => vec!["ssh", "home-files"],
+        Section::Symlinks(_) => vec!["symlinks", "home-files"],
+        // scripts can do anything, and directories are usually there for the sections after them
+        Section::Script(_) | Section::Ensure(_) => return Resources::Everything,
+        Section::Step(Step { sections, .. }) => {
+            let mut resources = vec![];
+            for section in sections {
+                match self::resources(section) {
+                    Resources::Everything => return Resources::Everything,
+                    Resources::Some(r) => resources.extend(r),
+                }
             }
+            resources
         }
+    };
+
+    Resources::Some(resources)
+}
+
+/// Whether `section` runs a tool that the system package manager might have installed, e.g. `cargo` or `age`
+fn uses_packages(section: &Section) -> bool {
+    match section {
+        Section::Rustup(_)
+        | Section::Cargo(_)
+        | Section::Go(_)
+        | Section::Npm(_)
+        | Section::Pipx(_)
+        | Section::Extract(_)
+        | Section::Fonts(_)
+        | Section::GitConfig(_)
+        | Section::Secrets(_)
+        | Section::Services(_) => true,
+        Section::Step(Step { sections, .. }) => sections.iter().any(uses_packages),
+        _ => false,
+    }
+}
 
+/// Whether `section` installs packages or adds the repositories they come from
+fn installs_packages(section: &Section) -> bool {
+    match section {
+        Section::Packages(_) | Section::Repositories(_) => true,
+        Section::Step(Step { sections, .. }) => sections.iter().any(installs_packages),
+        _ => false,
     }
 }
 
+/// Returns the wave each chunk can be installed in, where every chunk is in a later wave than the earlier chunks it depends on
+fn waves(chunks: &[(&Section, TokenStream)]) -> Vec<usize> {
+    let resources: Vec<_> = chunks.iter().map(|(section, _)| resources(section)).collect();
+    let mut waves: Vec<usize> = vec![];
+
+    for (i, (section, _)) in chunks.iter().enumerate() {
+        let wave = (0..i)
+            .filter(|&j| depends_on(section, &resources[i], chunks[j].0, &resources[j]))
+            .map(|j| waves[j] + 1)
+            .max()
+            .unwrap_or(0);
+        waves.push(wave);
+    }
+
+    waves
+}
+
+fn depends_on(section: &Section, resources: &Resources, earlier: &Section, earlier_resources: &Resources) -> bool {
+    if let (Section::Step(step), Section::Step(earlier)) = (section, earlier) {
+        let name = earlier.name.value();
+        if step.after.iter().chain(&step.requires).any(|n| n.value() == name) {
+            return true;
+        }
+    }
+
+    // the tools a section runs can come from earlier packages, but don't hold the package manager's lock themselves,
+    // so sections like these only wait for packages, and still run alongside each other
+    if uses_packages(section) && installs_packages(earlier) {
+        return true;
+    }
+
+    match (resources, earlier_resources) {
+        (Resources::Some(a), Resources::Some(b)) => a.iter().any(|r| b.contains(r)),
+        _ => true,
+    }
+}
+
+/// Moves every section matching `prerequisite` that appears after the first section matching `dependent`, or the first step
+/// containing one, to just before it, otherwise preserving the order of sections
+fn hoist(
+    sections: &mut Vec<&Section>,
+    prerequisite: fn(&Section) -> bool,
+    dependent: fn(&Section) -> bool,
+) {
+    let is_dependent = |section: &Section| match section {
+        Section::Step(Step { sections, .. }) => sections.iter().any(dependent),
+        section => dependent(section),
+    };
+
+    let first_dependent = match sections.iter().position(|s| is_dependent(s)) {
+        Some(index) => index,
+        None => return,
+    };
+
+    let (before, after) = sections.split_at(first_dependent);
+    let (hoisted, rest): (Vec<_>, Vec<_>) = after.iter().partition(|s| prerequisite(s));
+
+    *sections = before.iter().chain(&hoisted).chain(&rest).copied().collect();
+}
+
 fn generate_section(section: &Section) -> TokenStream {
     match section {
         Section::Cargo(Cargo { crates, .. }) => {
@@ -54,11 +482,20 @@ fn generate_section(section: &Section) -> TokenStream {
                 vec.push(::std::boxed::Box::new(temp));
             }
         },
-        Section::Ensure(Ensure {
-            absolute_paths,
-            home_paths,
-            ..
-        }) => {
+        Section::Cron(Cron { jobs, .. }) => {
+            let jobs = jobs
+                .iter()
+                .map(|CronJob { schedule, command, .. }| quote! { (#schedule.to_string(), #command.to_string()) });
+            quote! {
+                {
+                    let temp = ::dotinstall::Cron { jobs: ::std::vec![#(#jobs),*] };
+                    vec.push(::std::boxed::Box::new(temp));
+                }
+            }
+        }
+        Section::Ensure(Ensure { paths, .. }) => {
+            let (home_paths, absolute_paths): (Vec<_>, Vec<_>) =
+                paths.iter().partition(|lit| lit.value().starts_with('~'));
             let absolute_paths = absolute_paths.iter().map(|p| quote! { #p.into() });
             let home_paths = home_paths.iter().map(|p| quote! { #p.into() });
             quote! {
@@ -68,48 +505,209 @@ fn generate_section(section: &Section) -> TokenStream {
                 vec.push(::std::boxed::Box::new(temp));
             }
         },
+        Section::Extract(Extract { archives, .. }) => {
+            let archives = archives.iter().map(|Archive { archive, destination, format, strip, .. }| {
+                let format = match format {
+                    ArchiveFormat::TarGz => quote! { ::dotinstall::ArchiveFormat::TarGz },
+                    ArchiveFormat::TarXz => quote! { ::dotinstall::ArchiveFormat::TarXz },
+                    ArchiveFormat::Zip => quote! { ::dotinstall::ArchiveFormat::Zip },
+                };
+                quote! {
+                    ::dotinstall::Archive {
+                        archive: #archive.into(),
+                        destination: #destination.into(),
+                        format: #format,
+                        strip: #strip,
+                    }
+                }
+            });
+
+            quote! {
+                {
+                    let temp = ::dotinstall::Extract { archives: ::std::vec![#(#archives),*] };
+                    vec.push(::std::boxed::Box::new(temp));
+                }
+            }
+        }
+        Section::Aliases(_) | Section::Functions(_) => generate_shell(std::iter::once(section)),
+        Section::Fonts(Fonts { files, downloads, .. }) => {
+            let files = files.iter().map(|f| quote! { #f.to_string() });
+            let downloads = downloads.iter().map(|FontDownload { url, sha256, .. }| {
+                quote! {
+                    ::dotinstall::FontDownload {
+                        url: #url.to_string(),
+                        sha256: #sha256.to_string(),
+                    }
+                }
+            });
+
+            // only copies the files, the cache is refreshed by a `FontCache` at the end
+            quote! {
+                {
+                    let temp = ::dotinstall::Fonts {
+                        files: ::std::vec![#(#files),*],
+                        downloads: ::std::vec![#(#downloads),*],
+                    };
+                    vec.push(::std::boxed::Box::new(temp));
+                }
+            }
+        }
+        Section::GitConfig(GitConfig { file, entries, .. }) => {
+            let file = map_opt_into(file);
+            let entries = entries
+                .iter()
+                .map(|GitConfigEntry { key, value, .. }| quote! { (#key.to_string(), #value.to_string()) });
+            quote! {
+                {
+                    let temp = ::dotinstall::GitConfig {
+                        file: #file,
+                        entries: ::std::vec![#(#entries),*],
+                    };
+                    vec.push(::std::boxed::Box::new(temp));
+                }
+            }
+        }
+        Section::Go(Go { tools, .. }) => generate_tools(quote! { ::dotinstall::GoInstall }, tools),
+        Section::Npm(Npm { tools, .. }) => generate_tools(quote! { ::dotinstall::NpmInstall }, tools),
+        Section::Pipx(Pipx { tools, .. }) => generate_tools(quote! { ::dotinstall::PipxInstall }, tools),
+        Section::Repositories(Repositories {
+            apt,
+            brew,
+            pacman_aur,
+            ..
+        }) => {
+            let apt = apt.iter().map(|r| quote! { #r.to_string() });
+            let brew = brew.iter().map(|r| quote! { #r.to_string() });
+            let pacman_aur = map_opt(pacman_aur);
+            quote! {
+                {
+                    let temp = ::dotinstall::Repositories {
+                        apt: ::std::vec![#(#apt),*],
+                        brew: ::std::vec![#(#brew),*],
+                        pacman_aur: #pacman_aur,
+                    };
+                    vec.push(::std::boxed::Box::new(temp));
+                }
+            }
+        }
+        Section::Rustup(Rustup {
+            default,
+            toolchains,
+            components,
+            targets,
+            ..
+        }) => {
+            let default = map_opt(default);
+            let toolchains = toolchains.iter().map(|t| quote! { #t.to_string() });
+            let components = components.iter().map(|c| quote! { #c.to_string() });
+            let targets = targets.iter().map(|t| quote! { #t.to_string() });
+            quote! {
+                {
+                    let temp = ::dotinstall::Rustup {
+                        default: #default,
+                        toolchains: ::std::vec![#(#toolchains),*],
+                        components: ::std::vec![#(#components),*],
+                        targets: ::std::vec![#(#targets),*],
+                    };
+                    vec.push(::std::boxed::Box::new(temp));
+                }
+            }
+        }
         Section::Script(Script { path, .. }) => quote! {
             {
                 let temp = ::dotinstall::Script { path: #path.into() };
                 vec.push(::std::boxed::Box::new(temp));
             }
         },
-        Section::Packages(Packages { packages, .. }) => {
-            let mut build_vec = quote! {
-                let mut packages = ::std::vec![];
-            };
+        Section::Packages(Packages { packages, .. }) => generate_packages(packages.iter()),
+        // a step's sections are merged among themselves, but never with sections outside it
+        Section::Step(step) => generate_step(step, generate_sections(&step.sections)),
+        Section::Secrets(Secrets { identity, secrets, .. }) => {
+            let identity = map_opt_into(identity);
+            let secrets = secrets.iter().map(|Secret { source, destination, mode, .. }| {
+                quote! {
+                    ::dotinstall::Secret {
+                        source: #source.into(),
+                        destination: #destination.into(),
+                        mode: #mode,
+                    }
+                }
+            });
 
-            for Package {
-                name,
-                pacman,
-                apt,
-                brew,
-            } in packages
-            {
-                fn map_opt(o: &Option<LitStr>) -> TokenStream {
-                    o.as_ref()
-                        .map(|s| quote! { ::std::option::Option::Some(#s.to_string()) })
-                        .unwrap_or(quote! {::std::option::Option::None})
-                }
-
-                let apt = map_opt(apt);
-                let brew = map_opt(brew);
-                let pacman = map_opt(pacman);
-
-                build_vec.extend(quote! {
-                   packages.push(::dotinstall::Package {
-                      name: #name.to_string(),
-                      pacman: #pacman,
-                      apt: #apt,
-                      brew: #brew,
-                   });
-                });
+            quote! {
+                {
+                    let temp = ::dotinstall::Secrets {
+                        identity: #identity,
+                        secrets: ::std::vec![#(#secrets),*],
+                    };
+                    vec.push(::std::boxed::Box::new(temp));
+                }
             }
+        }
+        Section::Services(Services { units, .. }) => {
+            let units = units.iter().map(|Unit { scope, name, file, state, .. }| {
+                let scope = match scope {
+                    UnitScope::User => quote! { ::dotinstall::UnitScope::User },
+                    UnitScope::System => quote! { ::dotinstall::UnitScope::System },
+                };
+                let state = match state {
+                    UnitState::Enabled => quote! { ::dotinstall::UnitState::Enabled },
+                    UnitState::Started => quote! { ::dotinstall::UnitState::Started },
+                };
+                let file = map_opt_into(file);
+                quote! {
+                    ::dotinstall::Unit {
+                        scope: #scope,
+                        name: #name.to_string(),
+                        file: #file,
+                        state: #state,
+                    }
+                }
+            });
 
             quote! {
                 {
-                    #build_vec
-                    vec.push(::std::boxed::Box::new(packages));
+                    let temp = ::dotinstall::Services { units: ::std::vec![#(#units),*] };
+                    vec.push(::std::boxed::Box::new(temp));
+                }
+            }
+        }
+        Section::Ssh(Ssh { keys, hosts, .. }) => {
+            let keys = keys.iter().map(|SshKey { path, key_type, comment, .. }| {
+                let key_type = match key_type {
+                    SshKeyType::Ed25519 => quote! { ::dotinstall::SshKeyType::Ed25519 },
+                    SshKeyType::Ecdsa => quote! { ::dotinstall::SshKeyType::Ecdsa },
+                    SshKeyType::Rsa => quote! { ::dotinstall::SshKeyType::Rsa },
+                };
+                let comment = map_opt(comment);
+                quote! {
+                    ::dotinstall::SshKey {
+                        path: #path.into(),
+                        key_type: #key_type,
+                        comment: #comment,
+                    }
+                }
+            });
+            let hosts = hosts.iter().map(|SshHost { name, options, .. }| {
+                let options = options.iter().map(|(key, value)| {
+                    let key = key.to_string();
+                    quote! { (#key.to_string(), #value.to_string()) }
+                });
+                quote! {
+                    ::dotinstall::SshHost {
+                        name: #name.to_string(),
+                        options: ::std::vec![#(#options),*],
+                    }
+                }
+            });
+
+            quote! {
+                {
+                    let temp = ::dotinstall::Ssh {
+                        keys: ::std::vec![#(#keys),*],
+                        hosts: ::std::vec![#(#hosts),*],
+                    };
+                    vec.push(::std::boxed::Box::new(temp));
                 }
             }
         }
@@ -131,3 +729,557 @@ fn generate_section(section: &Section) -> TokenStream {
         }
     }
 }
+
+/// Generates a step holding the sections that `sections` pushes
+fn generate_step(Step { name, tags, requires, .. }: &Step, sections: TokenStream) -> TokenStream {
+    let tags = tags.iter().map(|t| t.to_string());
+    quote! {
+        {
+            let sections = {
+                let mut vec: ::std::vec::Vec<::std::boxed::Box<dyn ::dotinstall::Installable + ::std::marker::Send + ::std::marker::Sync>> = ::std::vec![];
+                #sections
+                vec
+            };
+            let temp = ::dotinstall::Step { name: #name, tags: &[#(#tags),*], requires: &[#(#requires),*], sections };
+            vec.push(::std::boxed::Box::new(temp));
+        }
+    }
+}
+
+/// Generates code that pushes the sections `uninstall` undoes onto `vec`. Only symlinks, `ensure` directories, crates and
+/// packages whose section opted in with `uninstall = true` are removed, everything else is left in place
+fn generate_uninstall_sections<'a>(sections: impl IntoIterator<Item = &'a Section>) -> TokenStream {
+    let sections = sections.into_iter().map(|section| match section {
+        Section::Symlinks(_) | Section::Ensure(_) | Section::Cargo(_) => generate_section(section),
+        Section::Packages(Packages { uninstall: true, packages, .. }) => generate_packages(packages.iter()),
+        Section::Step(step) => generate_step(step, generate_uninstall_sections(&step.sections)),
+        _ => quote! {},
+    });
+    quote! {
+        #(#sections)*
+    }
+}
+
+/// Generates associated consts describing what the installer declares, so it can be inspected without running anything
+fn generate_consts(sections: &[Section]) -> TokenStream {
+    let mut crates = vec![];
+    let mut symlinks = vec![];
+    let mut packages = vec![];
+    let mut dirs = vec![];
+
+    // each const lists an entry once, where it was first declared. parsing rejected packages that are declared twice
+    // with different options, so a package's name is enough to tell them apart
+    let mut seen = HashSet::new();
+
+    let sections = sections.iter().flat_map(|section| match section {
+        Section::Step(Step { sections, .. }) => sections.iter().collect(),
+        section => vec![section],
+    });
+
+    for section in sections {
+        match section {
+            Section::Cargo(Cargo { crates: c, .. }) => {
+                crates.extend(c.iter().filter(|c| seen.insert(("crate", c.value(), String::new()))));
+            }
+            Section::Symlinks(Symlinks { links, .. }) => {
+                symlinks.extend(
+                    links
+                        .iter()
+                        .filter(|Symlink { link, original, .. }| seen.insert(("symlink", link.value(), original.value())))
+                        .map(|Symlink { link, original, .. }| quote! { (#link, #original) }),
+                );
+            }
+            Section::Ensure(Ensure { paths, .. }) => {
+                dirs.extend(paths.iter().filter(|p| seen.insert(("dir", p.value(), String::new()))));
+            }
+            Section::Packages(Packages { packages: p, .. }) => {
+                for package in p.iter().filter(|p| seen.insert(("package", p.name.value(), String::new()))) {
+                    packages.push(generate_package_spec(package));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    quote! {
+        pub const CARGO_CRATES: &'static [&'static str] = &[#(#crates),*];
+        pub const SYMLINKS: &'static [(&'static str, &'static str)] = &[#(#symlinks),*];
+        pub const PACKAGES: &'static [::dotinstall::PackageSpec] = &[#(#packages),*];
+        pub const ENSURED_DIRS: &'static [&'static str] = &[#(#dirs),*];
+    }
+}
+
+fn generate_package_spec(package: &Package) -> TokenStream {
+    let Package { name, pacman, apt, brew, brew_cask, flatpak, snap, optional } = package;
+    let [pacman, apt, brew, brew_cask, flatpak, snap] = [pacman, apt, brew, brew_cask, flatpak, snap].map(|o| match o {
+        Some(s) => quote! { ::std::option::Option::Some(#s) },
+        None => quote! { ::std::option::Option::None },
+    });
+
+    quote! {
+        ::dotinstall::PackageSpec {
+            name: #name,
+            pacman: #pacman,
+            apt: #apt,
+            brew: #brew,
+            brew_cask: #brew_cask,
+            flatpak: #flatpak,
+            snap: #snap,
+            optional: #optional,
+        }
+    }
+}
+
+fn map_opt(o: &Option<LitStr>) -> TokenStream {
+    o.as_ref()
+        .map(|s| quote! { ::std::option::Option::Some(#s.to_string()) })
+        .unwrap_or(quote! {::std::option::Option::None})
+}
+
+fn map_opt_into(o: &Option<LitStr>) -> TokenStream {
+    o.as_ref()
+        .map(|s| quote! { ::std::option::Option::Some(#s.into()) })
+        .unwrap_or(quote! {::std::option::Option::None})
+}
+
+/// Generates an installation of `tools` using the given installer type, which has a single `tools` field
+fn generate_tools(installer: TokenStream, tools: &[Tool]) -> TokenStream {
+    let tools = tools.iter().map(|Tool { name, version }| {
+        let version = map_opt(version);
+        quote! {
+            ::dotinstall::Tool { name: #name.to_string(), version: #version }
+        }
+    });
+
+    quote! {
+        {
+            let temp = #installer { tools: ::std::vec![#(#tools),*] };
+            vec.push(::std::boxed::Box::new(temp));
+        }
+    }
+}
+
+/// Generates a single set of shell snippets for all `aliases` and `functions` in `sections`
+fn generate_shell<'a>(sections: impl Iterator<Item = &'a Section>) -> TokenStream {
+    let mut aliases = vec![];
+    let mut functions = vec![];
+
+    let snippet = |Snippet { name, body, .. }: &Snippet| quote! { (#name.to_string(), #body.to_string()) };
+
+    for section in sections {
+        match section {
+            Section::Aliases(a) => aliases.extend(a.aliases.iter().map(snippet)),
+            Section::Functions(f) => functions.extend(f.functions.iter().map(snippet)),
+            _ => {}
+        }
+    }
+
+    quote! {
+        {
+            let temp = ::dotinstall::ShellSnippets {
+                aliases: ::std::vec![#(#aliases),*],
+                functions: ::std::vec![#(#functions),*],
+            };
+            vec.push(::std::boxed::Box::new(temp));
+        }
+    }
+}
+
+/// Whether any of `sections`, including those inside steps, installs fonts
+fn has_fonts<'a>(sections: impl IntoIterator<Item = &'a Section>) -> bool {
+    sections.into_iter().any(|section| match section {
+        Section::Fonts(_) => true,
+        Section::Step(Step { sections, .. }) => has_fonts(sections),
+        _ => false,
+    })
+}
+
+/// Generates a single package installation for all required `packages`, followed by a separate installation for each optional package, skipping any package whose name has already been seen
+fn generate_packages<'a>(packages: impl Iterator<Item = &'a Package>) -> TokenStream {
+    let mut build_vec = quote! {
+        let mut packages = ::std::vec![];
+    };
+    let mut required = false;
+    let mut optional_packages = vec![];
+    let mut seen = HashSet::new();
+
+    // parsing already rejected duplicates that are installed differently, so the rest are the same package
+    for package in packages.filter(|p| seen.insert(p.name.value())) {
+        let Package {
+            name,
+            pacman,
+            apt,
+            brew,
+            brew_cask,
+            flatpak,
+            snap,
+            optional,
+        } = package;
+
+        let apt = map_opt(apt);
+        let brew = map_opt(brew);
+        let pacman = map_opt(pacman);
+        let brew_cask = map_opt(brew_cask);
+        let flatpak = map_opt(flatpak);
+        let snap = map_opt(snap);
+
+        let package = quote! {
+           ::dotinstall::Package {
+              name: #name.to_string(),
+              pacman: #pacman,
+              apt: #apt,
+              brew: #brew,
+              brew_cask: #brew_cask,
+              flatpak: #flatpak,
+              snap: #snap,
+           }
+        };
+
+        if *optional {
+            // installed on their own, so that a failure only affects that package.
+            // `Optional` hands the failure to `ctx.warn`, which collects it for the report at the end of the install
+            optional_packages.push(quote! {
+                let temp = ::dotinstall::Optional(::std::vec![#package]);
+                vec.push(::std::boxed::Box::new(temp));
+            });
+        } else {
+            required = true;
+            build_vec.extend(quote! {
+               packages.push(#package);
+            });
+        }
+    }
+
+    // the package manager isn't run at all when everything in the run is optional
+    let required_packages = required.then(|| quote! {
+        #build_vec
+        vec.push(::std::boxed::Box::new(packages));
+    });
+
+    quote! {
+        {
+            #required_packages
+            #(#optional_packages)*
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proc_macro2::TokenTree;
+    use syn::parse_str;
+
+    use super::*;
+
+    fn parse(input: &str) -> Installer {
+        parse_str(input).unwrap()
+    }
+
+    /// The `Installable` impl for an installer whose sections come from `install_sections()` and `uninstall_sections()`, without whitespace
+    fn generate_test_installable() -> String {
+        let name: Ident = parse_str("Laptop").unwrap();
+        let generated = generate_installable(
+            &name,
+            &Generics::default(),
+            quote! { install_sections() },
+            quote! { uninstall_sections() },
+            quote! {},
+        );
+        generated.to_string().split_whitespace().collect()
+    }
+
+    /// The generated code from `fn name(` up to the next `fn`
+    fn method(generated: &str, name: &str) -> String {
+        let start = generated.find(&format!("fn{name}(")).unwrap();
+        let end = generated[start + 2..].find("fn").map_or(generated.len(), |end| start + 2 + end);
+        generated[start..end].to_string()
+    }
+
+    /// The string literals in the value of the const called `name`
+    fn const_strings(consts: &TokenStream, name: &str) -> Vec<String> {
+        fn strings(tokens: TokenStream, out: &mut Vec<String>) {
+            for token in tokens {
+                match token {
+                    TokenTree::Group(group) => strings(group.stream(), out),
+                    TokenTree::Literal(lit) => {
+                        if let Ok(lit) = syn::parse2::<LitStr>(TokenTree::Literal(lit).into()) {
+                            out.push(lit.value());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let tokens: Vec<_> = consts.clone().into_iter().collect();
+        let start = tokens.iter().position(|t| matches!(t, TokenTree::Ident(i) if i == name)).unwrap();
+        let value = tokens[start..]
+            .iter()
+            .skip_while(|t| !matches!(t, TokenTree::Punct(p) if p.as_char() == '='))
+            .take_while(|t| !matches!(t, TokenTree::Punct(p) if p.as_char() == ';'))
+            .cloned()
+            .collect();
+
+        let mut out = vec![];
+        strings(value, &mut out);
+        out
+    }
+
+    /// The keyword of the section each chunk was generated from
+    fn kinds(chunks: &[(&Section, TokenStream)]) -> Vec<&'static str> {
+        chunks
+            .iter()
+            .map(|(section, _)| match section {
+                Section::Aliases(_) => "aliases",
+                Section::Cargo(_) => "cargo",
+                Section::Cron(_) => "cron",
+                Section::Ensure(_) => "ensure",
+                Section::Extract(_) => "extract",
+                Section::Fonts(_) => "fonts",
+                Section::Functions(_) => "functions",
+                Section::GitConfig(_) => "gitconfig",
+                Section::Go(_) => "go",
+                Section::Npm(_) => "npm",
+                Section::Packages(_) => "packages",
+                Section::Pipx(_) => "pipx",
+                Section::Repositories(_) => "repositories",
+                Section::Rustup(_) => "rustup",
+                Section::Script(_) => "exec",
+                Section::Secrets(_) => "secrets",
+                Section::Services(_) => "services",
+                Section::Ssh(_) => "ssh",
+                Section::Step(_) => "step",
+                Section::Symlinks(_) => "symlinks",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merges_packages_up_to_scripts() {
+        let installer = parse(
+            r#"
+            packages { "curl" };
+            symlinks { "foo" => "bar" };
+            packages { "git" };
+            exec "./add-docker-repo.sh";
+            packages { "docker-ce" };
+            "#,
+        );
+        let chunks = generate_chunks(&installer.sections);
+        assert_eq!(kinds(&chunks), ["packages", "symlinks", "exec", "packages"]);
+
+        let merged = chunks[0].1.to_string();
+        assert!(merged.contains("\"curl\"") && merged.contains("\"git\""));
+        assert!(!merged.contains("docker-ce"));
+    }
+
+    #[test]
+    fn refreshes_font_cache_once_at_the_end() {
+        let installer = parse(
+            r#"
+            fonts { "fonts/*.ttf" };
+            extract { "vendor/fonts.tar.gz" => "extracted" };
+            fonts { "extracted/*.ttf" };
+            step "gui" { fonts { "gui/*.otf" } };
+            symlinks { "foo" => "bar" };
+            "#,
+        );
+        let chunks = generate_chunks(&installer.sections);
+        assert_eq!(kinds(&chunks), ["fonts", "extract", "fonts", "step", "symlinks"]);
+
+        let generated = generate_installer(&installer).to_string();
+        assert_eq!(generated.matches("FontCache").count(), 1);
+        assert!(generated.find("FontCache") > generated.rfind("Symlinks"));
+
+        let installer = parse(r#"symlinks { "foo" => "bar" };"#);
+        assert!(!generate_installer(&installer).to_string().contains("FontCache"));
+    }
+
+    #[test]
+    fn hoists_repositories_before_packages() {
+        let installer = parse(
+            r#"
+            symlinks { "foo" => "bar" };
+            packages { "curl" };
+            exec "./setup.sh";
+            repositories { apt = ["ppa:git-core/ppa"] };
+            packages { "git" };
+            repositories { brew = ["homebrew/cask-fonts"] };
+            "#,
+        );
+        let mut sections: Vec<_> = installer.sections.iter().collect();
+        hoist(
+            &mut sections,
+            |s| matches!(s, Section::Repositories(_)),
+            |s| matches!(s, Section::Packages(_)),
+        );
+
+        let chunks: Vec<_> = sections.iter().map(|s| (*s, TokenStream::new())).collect();
+        assert_eq!(
+            kinds(&chunks),
+            ["symlinks", "repositories", "repositories", "packages", "exec", "packages"]
+        );
+    }
+
+    #[test]
+    fn hoists_prerequisites_before_steps_that_need_them() {
+        let installer = parse(
+            r#"
+            symlinks { "foo" => "bar" };
+            step "tools" { cargo { "ripgrep" } };
+            step "apps" { packages { "firefox" } };
+            rustup { default = "stable" };
+            repositories { apt = ["ppa:mozillateam/ppa"] };
+            "#,
+        );
+        let chunks = generate_chunks(&installer.sections);
+        assert_eq!(kinds(&chunks), ["symlinks", "rustup", "step", "repositories", "step"]);
+    }
+
+    #[test]
+    fn schedules_waves_by_shared_resources() {
+        let installer = parse(
+            r#"
+            packages { "curl" };
+            cron { "@daily" => "~/bin/backup" };
+            cargo { "ripgrep" };
+            npm { "prettier" };
+            pipx { "black" };
+            symlinks { "vimrc" => "~/.vimrc" };
+            extract { "vendor/nvim.tar.gz" => "~/.local/opt/nvim" };
+            aliases { g = "git" };
+            exec "./setup.sh";
+            "#,
+        );
+        let chunks = generate_chunks(&installer.sections);
+        assert_eq!(
+            kinds(&chunks),
+            ["packages", "cron", "cargo", "npm", "pipx", "symlinks", "extract", "aliases", "exec"]
+        );
+        assert_eq!(waves(&chunks), [0, 0, 1, 1, 1, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn waits_for_earlier_steps() {
+        let installer = parse(
+            r#"
+            step "a" { cron { "@daily" => "a" } };
+            step "b" after "a" { fonts { "fonts/*.ttf" } };
+            step "c" { go { "golang.org/x/tools/gopls" } };
+            step "d" { packages { "golang" } };
+            step "e" { go { "github.com/junegunn/fzf" } };
+            "#,
+        );
+        let chunks = generate_chunks(&installer.sections);
+        let resources: Vec<_> = chunks.iter().map(|(s, _)| resources(s)).collect();
+        let depends = |i: usize, j: usize| depends_on(chunks[i].0, &resources[i], chunks[j].0, &resources[j]);
+
+        assert!(depends(1, 0));
+        assert!(!depends(0, 1));
+        assert!(!depends(2, 1));
+        assert!(!depends(2, 0));
+        assert!(depends(4, 3));
+        assert!(!depends(3, 2));
+        assert_eq!(waves(&chunks), [0, 1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn lists_each_declaration_once_in_consts() {
+        let installer = parse(
+            r#"
+            ensure { "~/.local/bin", "/foo" };
+            cargo { "ripgrep", "exa" };
+            symlinks { "vimrc" => "~/.vimrc" };
+            packages { "curl" };
+            step "more" {
+                ensure { "/bar", "/foo" };
+                cargo { "ripgrep" };
+                symlinks { "vimrc" => "~/.vimrc", "zshrc" => "~/.zshrc" };
+                packages { "git", "curl" };
+            };
+            "#,
+        );
+        let consts = generate_consts(&installer.sections);
+
+        assert_eq!(const_strings(&consts, "ENSURED_DIRS"), ["~/.local/bin", "/foo", "/bar"]);
+        assert_eq!(const_strings(&consts, "CARGO_CRATES"), ["ripgrep", "exa"]);
+        assert_eq!(const_strings(&consts, "SYMLINKS"), ["vimrc", "~/.vimrc", "zshrc", "~/.zshrc"]);
+        assert_eq!(const_strings(&consts, "PACKAGES"), ["curl", "git"]);
+    }
+
+    #[test]
+    fn installs_optional_packages_on_their_own() {
+        let installer = parse(r#"packages { "nerd-fonts"?, "foo" => { optional = true } };"#);
+        let chunks = generate_chunks(&installer.sections);
+        let chunk = chunks[0].1.to_string();
+        assert_eq!(chunk.matches("Optional").count(), 2);
+        assert!(!chunk.contains("Box :: new (packages)"), "{chunk}");
+
+        let installer = parse(r#"packages { "nerd-fonts"?, "curl" };"#);
+        let chunks = generate_chunks(&installer.sections);
+        assert!(chunks[0].1.to_string().contains("Box :: new (packages)"));
+    }
+
+    #[test]
+    fn uninstalls_only_removable_sections() {
+        let installer = parse(
+            r#"
+            ensure { "~/.local/bin" };
+            packages { "curl" };
+            packages (uninstall = true) { "ripgrep" };
+            cargo { "exa" };
+            ssh { key "~/.ssh/id_ed25519" };
+            cron { "@daily" => "~/bin/backup" };
+            gitconfig { "user.name" = "Jane Doe" };
+            step "links" { symlinks { "foo" => "bar" }; secrets { "a.age" => "~/a" } };
+            "#,
+        );
+        let uninstall = generate_uninstall_sections(&installer.sections).to_string();
+
+        for removed in ["EnsureDirs", "\"ripgrep\"", "CargoInstall", "Step", "Symlinks"] {
+            assert!(uninstall.contains(removed), "{removed} in {uninstall}");
+        }
+        for kept in ["\"curl\"", "Ssh", "Cron", "GitConfig", "Secrets"] {
+            assert!(!uninstall.contains(kept), "{kept} in {uninstall}");
+        }
+    }
+
+    #[test]
+    fn reports_plans_on_dry_runs() {
+        let generated = generate_test_installable();
+
+        let install = method(&generated, "install");
+        assert!(install.contains("ifctx.dry_run(){ctx.report(::dotinstall::Installable::plan(self,ctx));return"));
+        let uninstall = method(&generated, "uninstall");
+        assert!(uninstall.contains("ifctx.dry_run(){ctx.report(::dotinstall::Installable::plan_uninstall(self,ctx));return"));
+        assert!(!generated.contains("print"));
+
+        let plan = method(&generated, "plan");
+        assert!(plan.contains("letsections=install_sections();"));
+        assert!(plan.contains("forsectioninsections.iter(){plan.extend(::dotinstall::Installable::plan(&**section,ctx));}"));
+        let plan_uninstall = method(&generated, "plan_uninstall");
+        assert!(plan_uninstall.contains("letsections=uninstall_sections();"));
+        assert!(plan_uninstall.contains("forsectioninsections.iter().rev(){plan.extend(::dotinstall::Installable::plan_uninstall(&**section,ctx));}"));
+    }
+
+    #[test]
+    fn checks_every_section() {
+        let generated = generate_test_installable();
+        let check = method(&generated, "check");
+        assert!(check.contains("letsections=install_sections();"));
+        assert!(check.contains("sections.iter().flat_map(|section|::dotinstall::Installable::check(&**section,ctx)).collect()"));
+
+        // the installer's own sections, not only the ones `uninstall` undoes
+        let installer = parse(r#"ssh { key "~/.ssh/id_ed25519" }; symlinks { "foo" => "bar" };"#);
+        let generated: String = generate_installer(&installer).to_string().split_whitespace().collect();
+        assert!(method(&generated, "check").contains("letsections=&self.sections;"));
+    }
+
+    #[test]
+    fn skips_duplicate_packages() {
+        let installer = parse(r#"packages { "foo", "bar" }; packages { "foo" };"#);
+        let chunks = generate_chunks(&installer.sections);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].1.to_string().matches("\"foo\"").count(), 1);
+    }
+}
@@ -1,14 +1,53 @@
+use std::path::PathBuf;
+
 use proc_macro2::TokenStream;
+use quote::quote;
 use syn::parse2;
 
-use self::codegen::generate_installer;
+use self::{
+    codegen::{generate_derive, generate_installer},
+    parse::{toml::TomlInstaller, Installer, InstallerInput},
+};
 
 mod codegen;
+// keyword and punctuation tokens are kept in the AST for their spans, even where nothing reads them
+#[allow(dead_code)]
 mod parse;
 
 pub fn installer(tokens: TokenStream) -> TokenStream {
     match parse2(tokens) {
-        Ok(installer) => generate_installer(&installer),
+        Ok(InstallerInput::Inline(installer)) => generate_installer(&installer),
+        Ok(InstallerInput::File(file)) => generate_loaded(file.load()),
+        Err(e) => e.to_compile_error(),
+    }
+}
+
+pub fn installer_from_toml(tokens: TokenStream) -> TokenStream {
+    match parse2::<TomlInstaller>(tokens) {
+        Ok(toml) => generate_loaded(toml.load()),
+        Err(e) => e.to_compile_error(),
+    }
+}
+
+pub fn derive_installer(tokens: TokenStream) -> TokenStream {
+    match parse2(tokens) {
+        Ok(derive) => generate_derive(&derive),
+        Err(e) => e.to_compile_error(),
+    }
+}
+
+/// Generates an installer that was loaded from a file
+fn generate_loaded(installer: syn::Result<(Installer, PathBuf)>) -> TokenStream {
+    match installer {
+        Ok((installer, path)) => {
+            let installer = generate_installer(&installer);
+            let path = path.to_string_lossy();
+            // including the file makes cargo rebuild when it changes
+            quote! {
+                #installer
+                const _: &[u8] = ::std::include_bytes!(#path);
+            }
+        }
         Err(e) => e.to_compile_error(),
     }
 }
@@ -0,0 +1,277 @@
+use syn::{braced, parse::Parse, punctuated::Punctuated, Error, LitStr, Token};
+
+use super::kw;
+
+pub struct Cron {
+    pub cron_kw: kw::cron,
+    pub jobs: Vec<CronJob>,
+}
+
+impl Parse for Cron {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let cron_kw = input.parse()?;
+        let content;
+        let _ = braced!(content in input);
+
+        let jobs = Punctuated::<CronJob, Token![,]>::parse_terminated(&content)?;
+        let jobs = jobs.into_iter().collect();
+
+        Ok(Self { cron_kw, jobs })
+    }
+}
+
+pub struct CronJob {
+    pub schedule: LitStr,
+    pub arrow: Token![=>],
+    pub command: LitStr,
+}
+
+impl Parse for CronJob {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let schedule: LitStr = input.parse()?;
+        validate_schedule(&schedule.value())
+            .map_err(|e| Error::new(schedule.span(), format!("invalid cron schedule: {e}")))?;
+
+        let arrow = input.parse()?;
+        let command: LitStr = input.parse()?;
+        if command.value().contains('\n') {
+            return Err(Error::new(
+                command.span(),
+                "cron commands must be on a single line",
+            ));
+        }
+        if has_unescaped_percent(&command.value()) {
+            return Err(Error::new(
+                command.span(),
+                "cron turns `%` in commands into newlines, so it must be escaped as `\\%`",
+            ));
+        }
+
+        Ok(Self {
+            schedule,
+            arrow,
+            command,
+        })
+    }
+}
+
+fn has_unescaped_percent(command: &str) -> bool {
+    let mut escaped = false;
+    for c in command.chars() {
+        if c == '%' && !escaped {
+            return true;
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    false
+}
+
+const MACROS: &[&str] = &[
+    "@reboot",
+    "@yearly",
+    "@annually",
+    "@monthly",
+    "@weekly",
+    "@daily",
+    "@midnight",
+    "@hourly",
+];
+
+const MONTHS: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+const DAYS: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+struct Field {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    /// Names that can be used instead of numbers, starting at `min`
+    names: &'static [&'static str],
+}
+
+const FIELDS: [Field; 5] = [
+    Field {
+        name: "minute",
+        min: 0,
+        max: 59,
+        names: &[],
+    },
+    Field {
+        name: "hour",
+        min: 0,
+        max: 23,
+        names: &[],
+    },
+    Field {
+        name: "day of month",
+        min: 1,
+        max: 31,
+        names: &[],
+    },
+    Field {
+        name: "month",
+        min: 1,
+        max: 12,
+        names: MONTHS,
+    },
+    Field {
+        name: "day of week",
+        min: 0,
+        max: 7,
+        names: DAYS,
+    },
+];
+
+/// Checks that `schedule` is either one of the `@` macros, or 5 whitespace separated fields in crontab(5) syntax
+fn validate_schedule(schedule: &str) -> Result<(), String> {
+    let schedule = schedule.trim();
+    if schedule.starts_with('@') {
+        return match MACROS.contains(&schedule) {
+            true => Ok(()),
+            false => Err(format!("unknown schedule `{schedule}`")),
+        };
+    }
+
+    let parts: Vec<_> = schedule.split_whitespace().collect();
+    if parts.len() != FIELDS.len() {
+        return Err(format!(
+            "expected {} fields but found {}",
+            FIELDS.len(),
+            parts.len()
+        ));
+    }
+
+    for (part, field) in parts.iter().zip(&FIELDS) {
+        for item in part.split(',') {
+            validate_item(item, field)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_item(item: &str, field: &Field) -> Result<(), String> {
+    let (range, step) = match item.split_once('/') {
+        Some((range, step)) => (range, Some(step)),
+        None => (item, None),
+    };
+
+    if let Some(step) = step {
+        match step.parse::<u32>() {
+            Ok(step) if step > 0 => {}
+            _ => return Err(format!("invalid step `{step}` in {} field", field.name)),
+        }
+    }
+
+    if range == "*" {
+        return Ok(());
+    }
+
+    match range.split_once('-') {
+        Some((start, end)) => {
+            let start = parse_value(start, field)?;
+            let end = parse_value(end, field)?;
+            if start > end {
+                return Err(format!("range `{range}` in {} field is backwards", field.name));
+            }
+        }
+        None => {
+            parse_value(range, field)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_value(value: &str, field: &Field) -> Result<u32, String> {
+    if let Some(index) = field
+        .names
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(value))
+    {
+        return Ok(field.min + index as u32);
+    }
+
+    match value.parse::<u32>() {
+        Ok(n) if (field.min..=field.max).contains(&n) => Ok(n),
+        Ok(_) => Err(format!(
+            "{} `{value}` is out of range {}-{}",
+            field.name, field.min, field.max
+        )),
+        Err(_) => Err(format!("invalid {} `{value}`", field.name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+
+    #[test]
+    fn parses_cron_section() {
+        let cron: Cron = parse_str(
+            r#"cron {
+                "0 * * * *" => "~/bin/sync-notes",
+                "@daily" => "~/bin/backup",
+            }"#,
+        )
+        .unwrap();
+
+        let jobs: Vec<_> = cron
+            .jobs
+            .iter()
+            .map(|j| (j.schedule.value(), j.command.value()))
+            .collect();
+        assert_eq!(
+            jobs,
+            [
+                ("0 * * * *".to_string(), "~/bin/sync-notes".to_string()),
+                ("@daily".to_string(), "~/bin/backup".to_string()),
+            ]
+        );
+
+        assert!(parse_str::<Cron>(r#"cron { "60 * * * *" => "foo" }"#).is_err());
+        assert!(parse_str::<Cron>(r#"cron { "* * * * *" => "foo\nbar" }"#).is_err());
+        assert!(parse_str::<Cron>(r#"cron { "@daily" => "tar czf ~/$(date +%F).tgz ~/notes" }"#).is_err());
+        assert!(parse_str::<Cron>(r#"cron { "@daily" => "date +\\%F >> ~/log" }"#).is_ok());
+        assert!(parse_str::<Cron>(r#"cron { "@daily" => "echo \\\\%" }"#).is_err());
+    }
+
+    #[test]
+    fn validates_schedules() {
+        for valid in [
+            "* * * * *",
+            "*/15 * * * *",
+            "0 9-17 * * mon-fri",
+            "30 2 1,15 * *",
+            "0 0 * JAN,jul 0",
+            "0 0 * * 7",
+            "5-55/10 */2 * * *",
+            "@hourly",
+            "@reboot",
+        ] {
+            assert_eq!(validate_schedule(valid), Ok(()), "{valid}");
+        }
+
+        for invalid in [
+            "",
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "10-5 * * * *",
+            "* * * foo *",
+            "@fortnightly",
+            "a,b * * * *",
+        ] {
+            assert!(validate_schedule(invalid).is_err(), "{invalid}");
+        }
+    }
+}
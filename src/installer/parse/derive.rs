@@ -0,0 +1,193 @@
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use quote::quote;
+use syn::{parse::Parse, parse2, Data, DeriveInput, Error, Generics, Ident, Index, Member};
+
+use super::{package::check_duplicate_packages, Section, SECTION_KEYWORDS};
+
+/// A struct deriving `Installer`, whose fields are annotated with sections, e.g. `#[cargo("ripgrep")]`, or with `#[nested]` if they hold another `Installable`
+pub struct DeriveInstaller {
+    pub name: Ident,
+    pub generics: Generics,
+    pub entries: Vec<Entry>,
+}
+
+pub enum Entry {
+    Section(Section),
+    Nested(Member),
+}
+
+impl Parse for DeriveInstaller {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let DeriveInput {
+            ident,
+            generics,
+            data,
+            ..
+        } = input.parse()?;
+
+        let fields = match data {
+            Data::Struct(s) => s.fields,
+            _ => {
+                return Err(Error::new(
+                    ident.span(),
+                    "`Installer` can only be derived for structs",
+                ))
+            }
+        };
+
+        let mut entries = vec![];
+
+        for (index, field) in fields.iter().enumerate() {
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(index)),
+            };
+
+            for attr in &field.attrs {
+                if attr.path.is_ident("nested") {
+                    if !attr.tokens.is_empty() {
+                        return Err(Error::new_spanned(&attr.tokens, "expected `#[nested]`"));
+                    }
+                    entries.push(Entry::Nested(member.clone()));
+                    continue;
+                }
+
+                let keyword = match attr.path.get_ident() {
+                    Some(ident) if SECTION_KEYWORDS.contains(&ident.to_string().as_str()) => ident,
+                    _ => continue,
+                };
+
+                let args: TokenStream = attr.parse_args()?;
+                // `#[exec("./script.sh")]` is the only section that isn't written with braces, unless they're
+                // already there after a gitconfig file or options, e.g. `#[secrets((identity = "key.txt") { .. })]`
+                let tokens = if keyword == "exec" || has_braces(&args) {
+                    quote! { #keyword #args }
+                } else {
+                    quote! { #keyword { #args } }
+                };
+
+                entries.push(Entry::Section(parse2(tokens)?));
+            }
+        }
+
+        check_duplicate_packages(entries.iter().filter_map(|entry| match entry {
+            Entry::Section(section) => Some(section),
+            Entry::Nested(_) => None,
+        }))?;
+
+        Ok(Self {
+            name: ident,
+            generics,
+            entries,
+        })
+    }
+}
+
+/// Whether `args` is a string or parenthesized options followed by the braced body of a section
+fn has_braces(args: &TokenStream) -> bool {
+    let args: Vec<_> = args.clone().into_iter().collect();
+    match &args[..] {
+        [TokenTree::Literal(_), TokenTree::Group(body)] => body.delimiter() == Delimiter::Brace,
+        [TokenTree::Group(options), TokenTree::Group(body)] => {
+            options.delimiter() == Delimiter::Parenthesis && body.delimiter() == Delimiter::Brace
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+
+    #[test]
+    fn parses_derive_input() {
+        let derive: DeriveInstaller = parse_str(
+            r#"
+            #[derive(Default)]
+            struct Workstation {
+                name: String,
+                #[cargo("ripgrep", "exa")]
+                #[exec("./install_fonts.sh")]
+                tools: (),
+                #[nested]
+                base: Base,
+                #[symlinks("foo" => "bar")]
+                links: (),
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(derive.name, "Workstation");
+        assert_eq!(derive.entries.len(), 4);
+
+        let cargo = match &derive.entries[0] {
+            Entry::Section(Section::Cargo(cargo)) => cargo,
+            _ => panic!("expected a cargo section"),
+        };
+        assert_eq!(cargo.crates[1].value(), "exa");
+        assert!(matches!(
+            &derive.entries[1],
+            Entry::Section(Section::Script(script)) if script.path.value() == "./install_fonts.sh"
+        ));
+        assert!(matches!(
+            &derive.entries[2],
+            Entry::Nested(Member::Named(ident)) if ident == "base"
+        ));
+        assert!(matches!(&derive.entries[3], Entry::Section(Section::Symlinks(_))));
+    }
+
+    #[test]
+    fn passes_braced_sections_through() {
+        let derive: DeriveInstaller = parse_str(
+            r#"
+            struct Workstation {
+                #[gitconfig("~/.gitconfig.local" { "user.name" = "me" })]
+                #[secrets((identity = "~/.age/key.txt") { "secrets/netrc.age" => "~/.netrc" })]
+                #[packages("firefox" => { flatpak = "org.mozilla.firefox" })]
+                #[gitconfig("user.email" = "me@example.com")]
+                config: (),
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            &derive.entries[0],
+            Entry::Section(Section::GitConfig(g)) if g.file.as_ref().unwrap().value() == "~/.gitconfig.local"
+        ));
+        assert!(matches!(
+            &derive.entries[1],
+            Entry::Section(Section::Secrets(s)) if s.identity.as_ref().unwrap().value() == "~/.age/key.txt"
+        ));
+        assert!(matches!(&derive.entries[2], Entry::Section(Section::Packages(_))));
+        assert!(matches!(
+            &derive.entries[3],
+            Entry::Section(Section::GitConfig(g)) if g.file.is_none()
+        ));
+    }
+
+    #[test]
+    fn parses_tuple_struct() {
+        let derive: DeriveInstaller =
+            parse_str(r#"struct Both(#[nested] Laptop, #[nested] Server);"#).unwrap();
+        assert!(matches!(
+            &derive.entries[..],
+            [Entry::Nested(Member::Unnamed(first)), Entry::Nested(Member::Unnamed(second))]
+                if first.index == 0 && second.index == 1
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_derive_input() {
+        assert!(parse_str::<DeriveInstaller>(r#"enum Foo { #[cargo("ripgrep")] A }"#).is_err());
+        assert!(parse_str::<DeriveInstaller>(r#"struct Foo { #[cargo(ripgrep)] a: () }"#).is_err());
+        assert!(parse_str::<DeriveInstaller>(r#"struct Foo { #[nested(true)] a: () }"#).is_err());
+        assert!(parse_str::<DeriveInstaller>(
+            r#"struct Foo { #[packages("foo")] a: (), #[packages("foo"?)] b: () }"#
+        )
+        .is_err());
+    }
+}
@@ -5,8 +5,8 @@ use super::kw;
 
 pub struct Ensure {
     pub ensure_kw: kw::ensure,
-    pub absolute_paths: Vec<LitStr>,
-    pub home_paths: Vec<LitStr>,
+    /// Every path, in the order they were written. Paths starting with `~` are relative to the home directory
+    pub paths: Vec<LitStr>,
 }
 
 impl Parse for Ensure {
@@ -16,14 +16,10 @@ impl Parse for Ensure {
         let _ = braced!(content in input);
 
         let paths = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
-        let (home_paths, absolute_paths) = paths
-            .into_iter()
-            .partition(|lit| lit.value().starts_with('~'));
 
         Ok(Self {
             ensure_kw,
-            home_paths,
-            absolute_paths,
+            paths: paths.into_iter().collect(),
         })
     }
 }
@@ -46,9 +42,7 @@ mod tests {
         )
         .unwrap();
 
-        let home_paths: Vec<_> = ensure.home_paths.iter().map(|lit| lit.value()).collect();
-        assert_eq!(home_paths, vec!["~/third"]);
-        let absolute_paths: Vec<_> = ensure.absolute_paths.iter().map(|lit| lit.value()).collect();
-        assert_eq!(absolute_paths, vec!["first", "second"]);
+        let paths: Vec<_> = ensure.paths.iter().map(|lit| lit.value()).collect();
+        assert_eq!(paths, vec!["first", "second", "~/third"]);
     }
 }
@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+
+use syn::{braced, parenthesized, parse::Parse, punctuated::Punctuated, Error, LitInt, LitStr, Token};
+
+use super::kw;
+
+pub struct Extract {
+    pub extract_kw: kw::extract,
+    pub archives: Vec<Archive>,
+}
+
+impl Parse for Extract {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let extract_kw = input.parse()?;
+        let content;
+        let _ = braced!(content in input);
+
+        let archives = Punctuated::<Archive, Token![,]>::parse_terminated(&content)?;
+
+        let mut destination_set = HashSet::new();
+
+        for archive in &archives {
+            if !destination_set.insert(archive.destination.value()) {
+                return Err(Error::new(
+                    archive.destination.span(),
+                    format!(
+                        "Duplicate extraction into `{}`",
+                        archive.destination.value()
+                    ),
+                ));
+            }
+        }
+
+        let archives = archives.into_iter().collect();
+
+        Ok(Self {
+            extract_kw,
+            archives,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    TarGz,
+    TarXz,
+    Zip,
+}
+
+impl ArchiveFormat {
+    fn from_path(path: &str) -> Option<Self> {
+        let path = path.to_ascii_lowercase();
+        if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if path.ends_with(".tar.xz") || path.ends_with(".txz") {
+            Some(Self::TarXz)
+        } else if path.ends_with(".zip") {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct Archive {
+    pub archive: LitStr,
+    pub arrow: Token![=>],
+    pub destination: LitStr,
+    pub format: ArchiveFormat,
+    /// The number of leading path components to remove from each extracted file
+    pub strip: u32,
+}
+
+impl Parse for Archive {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let archive: LitStr = input.parse()?;
+        let format = ArchiveFormat::from_path(&archive.value()).ok_or_else(|| {
+            Error::new(
+                archive.span(),
+                "unsupported archive, expected a `.tar.gz`, `.tar.xz` or `.zip` file",
+            )
+        })?;
+
+        let arrow = input.parse()?;
+        let destination = input.parse()?;
+
+        let mut strip = 0;
+        if input.peek(syn::token::Paren) {
+            let content;
+            let _ = parenthesized!(content in input);
+            content.parse::<kw::strip>()?;
+            content.parse::<Token![=]>()?;
+            strip = content.parse::<LitInt>()?.base10_parse()?;
+            let _: Option<Token![,]> = content.parse()?;
+        }
+
+        Ok(Self {
+            archive,
+            arrow,
+            destination,
+            format,
+            strip,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+
+    #[test]
+    fn parses_extract_section() {
+        let extract: Extract = parse_str(
+            r#"extract {
+                "vendor/nvim-linux64.tar.gz" => "~/.local/opt/nvim" (strip = 1),
+                "vendor/zig.tar.xz" => "~/.local/opt/zig",
+                "vendor/Tool.ZIP" => "~/.local/opt/tool",
+            }"#,
+        )
+        .unwrap();
+
+        let archives: Vec<_> = extract
+            .archives
+            .iter()
+            .map(|a| (a.archive.value(), a.destination.value(), a.format, a.strip))
+            .collect();
+        assert_eq!(
+            archives,
+            [
+                (
+                    "vendor/nvim-linux64.tar.gz".to_string(),
+                    "~/.local/opt/nvim".to_string(),
+                    ArchiveFormat::TarGz,
+                    1
+                ),
+                (
+                    "vendor/zig.tar.xz".to_string(),
+                    "~/.local/opt/zig".to_string(),
+                    ArchiveFormat::TarXz,
+                    0
+                ),
+                (
+                    "vendor/Tool.ZIP".to_string(),
+                    "~/.local/opt/tool".to_string(),
+                    ArchiveFormat::Zip,
+                    0
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_archives() {
+        assert!(parse_str::<Extract>(r#"extract { "vendor/nvim.tar.bz2" => "~/nvim" }"#).is_err());
+        assert!(parse_str::<Extract>(r#"extract { "a.zip" => "~/a" (strip = -1) }"#).is_err());
+        assert!(parse_str::<Extract>(r#"extract { "a.zip" => "~/a" (depth = 1) }"#).is_err());
+        assert!(parse_str::<Extract>(r#"extract { "a.zip" => "~/a", "b.zip" => "~/a" }"#).is_err());
+    }
+}
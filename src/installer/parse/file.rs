@@ -0,0 +1,178 @@
+use std::{
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use syn::{parse::Parse, parse_str, Error, LitStr, Token};
+
+use super::{kw, Installer};
+
+/// `file = "path"`, which loads the installer from a file relative to `CARGO_MANIFEST_DIR`
+pub struct InstallerFile {
+    pub file_kw: kw::file,
+    pub path: LitStr,
+}
+
+impl InstallerFile {
+    pub fn peek(input: syn::parse::ParseStream) -> bool {
+        input.peek(kw::file) && input.peek2(Token![=])
+    }
+
+    /// Reads and parses the file, returning the installer along with the absolute path that was read
+    pub fn load(&self) -> syn::Result<(Installer, PathBuf)> {
+        let (contents, path) = read(&self.path)?;
+
+        let installer = parse_contents(&contents).map_err(|errors| locate(&self.path, &errors))?;
+
+        Ok((installer, path))
+    }
+}
+
+impl Parse for InstallerFile {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let file_kw = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let path = input.parse()?;
+
+        Ok(Self { file_kw, path })
+    }
+}
+
+/// Reads the file at `path` relative to `CARGO_MANIFEST_DIR`, returning its contents and absolute path
+pub fn read(path: &LitStr) -> syn::Result<(String, PathBuf)> {
+    let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let absolute = PathBuf::from(manifest_dir).join(path.value());
+
+    let contents = std::fs::read_to_string(&absolute).map_err(|e| {
+        Error::new(
+            path.span(),
+            format!("couldn't read `{}`: {e}", absolute.display()),
+        )
+    })?;
+
+    Ok((contents, absolute))
+}
+
+/// Converts errors in the file at `path` into errors on `path` itself, with the 1-based line and column in the file in their message
+pub fn locate(path: &LitStr, errors: &[(usize, usize, String)]) -> Error {
+    let mut errors = errors.iter().map(|(line, column, message)| {
+        Error::new(
+            path.span(),
+            format!("{}:{line}:{column}: {message}", path.value()),
+        )
+    });
+
+    let mut error = errors.next().expect("parsing failed without an error");
+    error.extend(errors);
+    error
+}
+
+static FALLBACK: Mutex<()> = Mutex::new(());
+
+/// Switches proc-macro2 to its fallback implementation until dropped, even if parsing panics.
+///
+/// The switch is global to this crate's copy of proc-macro2, and `fallback::force` is a hidden API meant for cases like this one.
+/// rustc expands macros one at a time, so nothing else in the crate runs while it's switched, and only the rare failed
+/// parse of a loaded file pays for it. The lock keeps two loads from switching back under each other if that ever changes
+struct Fallback {
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Fallback {
+    fn force() -> Self {
+        let lock = FALLBACK.lock().unwrap_or_else(PoisonError::into_inner);
+        proc_macro2::fallback::force();
+        Fallback { _lock: lock }
+    }
+}
+
+impl Drop for Fallback {
+    fn drop(&mut self) {
+        proc_macro2::fallback::unforce();
+    }
+}
+
+/// Parses an installer from `contents`, returning the 1-based line and column of each error if it fails
+pub fn parse_contents(contents: &str) -> Result<Installer, Vec<(usize, usize, String)>> {
+    if let Ok(installer) = parse_str(contents) {
+        return Ok(installer);
+    }
+
+    // the compiler's spans don't know where they are on stable, so the errors are found again using
+    // proc-macro2's own implementation, which keeps track of lines and columns
+    let fallback = Fallback::force();
+    let errors = match parse_str::<Installer>(contents) {
+        Ok(_) => vec![],
+        Err(e) => e
+            .into_iter()
+            .map(|e| {
+                let (start, end) = (e.span().start(), e.span().end());
+                // errors at the end of the input have an empty span at the very start, so they're moved to the end
+                let (line, column) = if start == end && start.line == 1 && start.column == 0 {
+                    end_of(contents)
+                } else {
+                    (start.line, start.column + 1)
+                };
+                (line, column, e.to_string())
+            })
+            .collect(),
+    };
+    drop(fallback);
+
+    Err(errors)
+}
+
+/// The 1-based line and column just past the end of `contents`
+fn end_of(contents: &str) -> (usize, usize) {
+    let line = contents.matches('\n').count() + 1;
+    let column = contents.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+
+    #[test]
+    fn parses_file_contents() {
+        let installer = parse_contents(
+            r#"
+            cargo { "ripgrep" };
+            symlinks { "foo" => "bar" };
+            "#,
+        )
+        .ok()
+        .unwrap();
+        assert_eq!(installer.sections.len(), 2);
+    }
+
+    #[test]
+    fn reports_error_locations() {
+        let errors = parse_contents("cargo { \"ripgrep\" };\nensure {\n    \"~/bin\",\n    42,\n};\n")
+            .err()
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        let (line, column, message) = &errors[0];
+        assert_eq!((*line, *column), (4, 5));
+        assert_eq!(message, "expected string literal");
+
+        let file: InstallerFile = parse_str(r#"file = "dotfiles.install""#).unwrap();
+        let error = locate(&file.path, &errors);
+        assert_eq!(
+            error.to_string(),
+            "dotfiles.install:4:5: expected string literal"
+        );
+
+        let errors = parse_contents("cargo { \"ripgrep\" };\n\ncargo").err().unwrap();
+        let (line, column, message) = &errors[0];
+        assert_eq!((*line, *column), (3, 6));
+        assert_eq!(message, "unexpected end of input, expected curly braces");
+
+        let errors = parse_contents("packages { \"foo\" };\npackages {\n    \"foo\"?,\n};\n").err().unwrap();
+        let (line, column, message) = &errors[0];
+        assert_eq!((*line, *column), (3, 5));
+        assert_eq!(message, "Duplicate package `foo` with different options");
+    }
+}
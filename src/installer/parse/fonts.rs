@@ -0,0 +1,125 @@
+use syn::{braced, parse::Parse, punctuated::Punctuated, Error, LitStr, Token};
+
+use super::kw;
+
+pub struct Fonts {
+    pub fonts_kw: kw::fonts,
+    /// Glob patterns of font files in the dotfiles repo
+    pub files: Vec<LitStr>,
+    pub downloads: Vec<FontDownload>,
+}
+
+impl Parse for Fonts {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let fonts_kw = input.parse()?;
+        let content;
+        let _ = braced!(content in input);
+
+        let entries = Punctuated::<Entry, Token![,]>::parse_terminated(&content)?;
+
+        let mut files = vec![];
+        let mut downloads = vec![];
+        for entry in entries {
+            match entry {
+                Entry::File(file) => files.push(file),
+                Entry::Download(download) => downloads.push(download),
+            }
+        }
+
+        Ok(Self {
+            fonts_kw,
+            files,
+            downloads,
+        })
+    }
+}
+
+enum Entry {
+    File(LitStr),
+    Download(FontDownload),
+}
+
+impl Parse for Entry {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(kw::download) {
+            Ok(Self::Download(input.parse()?))
+        } else {
+            Ok(Self::File(input.parse()?))
+        }
+    }
+}
+
+/// An archive or font file that is downloaded and checked against its hash before installing
+pub struct FontDownload {
+    pub download_kw: kw::download,
+    pub url: LitStr,
+    pub sha256_kw: kw::sha256,
+    pub sha256: LitStr,
+}
+
+impl Parse for FontDownload {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let download_kw = input.parse()?;
+        let url = input.parse()?;
+        let sha256_kw = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let sha256: LitStr = input.parse()?;
+
+        let hash = sha256.value();
+        if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Error::new(
+                sha256.span(),
+                "expected a sha256 hash of 64 hex characters",
+            ));
+        }
+
+        Ok(Self {
+            download_kw,
+            url,
+            sha256_kw,
+            sha256,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+
+    const HASH: &str = "6f3a3f5a7e3ccb2a4ad8c0a5d7dc6d1e0b7fbad9ed5d5d0b4c4fcbf5d2a1b3c4";
+
+    #[test]
+    fn parses_fonts_section() {
+        let fonts: Fonts = parse_str(&format!(
+            r#"fonts {{
+                "fonts/FiraCode/*.ttf",
+                download "https://example.com/JetBrainsMono.zip" sha256 = "{HASH}",
+                "fonts/Inter/*.otf",
+            }}"#
+        ))
+        .unwrap();
+
+        let files: Vec<_> = fonts.files.iter().map(|f| f.value()).collect();
+        assert_eq!(files, ["fonts/FiraCode/*.ttf", "fonts/Inter/*.otf"]);
+
+        let download = &fonts.downloads[0];
+        assert_eq!(download.url.value(), "https://example.com/JetBrainsMono.zip");
+        assert_eq!(download.sha256.value(), HASH);
+    }
+
+    #[test]
+    fn rejects_invalid_downloads() {
+        assert!(parse_str::<Fonts>(r#"fonts { download "https://example.com/a.zip" }"#).is_err());
+        assert!(
+            parse_str::<Fonts>(r#"fonts { download "https://example.com/a.zip" sha256 = "abc" }"#)
+                .is_err()
+        );
+        let not_hex = "z".repeat(64);
+        assert!(parse_str::<Fonts>(&format!(
+            r#"fonts {{ download "https://example.com/a.zip" sha256 = "{not_hex}" }}"#
+        ))
+        .is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use syn::{braced, parse::Parse, punctuated::Punctuated, Error, LitStr, Token};
+
+use super::kw;
+
+pub struct GitConfig {
+    pub gitconfig_kw: kw::gitconfig,
+    /// The config file to write to, or the global config if not given
+    pub file: Option<LitStr>,
+    /// Keys may be repeated to set multi-valued entries such as `include.path`
+    pub entries: Vec<GitConfigEntry>,
+}
+
+impl Parse for GitConfig {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let gitconfig_kw = input.parse()?;
+        let file = if input.peek(LitStr) {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
+        let content;
+        let _ = braced!(content in input);
+
+        let entries = Punctuated::<GitConfigEntry, Token![,]>::parse_terminated(&content)?;
+        let entries = entries.into_iter().collect();
+
+        Ok(Self {
+            gitconfig_kw,
+            file,
+            entries,
+        })
+    }
+}
+
+pub struct GitConfigEntry {
+    pub key: LitStr,
+    pub eq: Token![=],
+    pub value: LitStr,
+}
+
+impl Parse for GitConfigEntry {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let key: LitStr = input.parse()?;
+        let eq = input.parse()?;
+        let value = input.parse()?;
+
+        // git requires at least `section.name`, with an optional subsection in between
+        let k = key.value();
+        let valid = match (k.find('.'), k.rfind('.')) {
+            (Some(first), Some(last)) => first > 0 && last < k.len() - 1,
+            _ => false,
+        };
+        if !valid {
+            return Err(Error::new(
+                key.span(),
+                format!("`{k}` is not a valid git config key, expected `section.name`"),
+            ));
+        }
+
+        Ok(Self { key, eq, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+
+    #[test]
+    fn parses_gitconfig_section() {
+        let config: GitConfig = parse_str(
+            r#"gitconfig {
+                "user.name" = "Jane Doe",
+                "core.pager" = "delta",
+                "include.path" = "~/.config/git/work",
+                "include.path" = "~/.config/git/personal",
+                "url.git@github.com:.insteadOf" = "https://github.com/",
+            }"#,
+        )
+        .unwrap();
+
+        assert!(config.file.is_none());
+        let entries: Vec<_> = config
+            .entries
+            .iter()
+            .map(|e| (e.key.value(), e.value.value()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("user.name".to_string(), "Jane Doe".to_string()),
+                ("core.pager".to_string(), "delta".to_string()),
+                ("include.path".to_string(), "~/.config/git/work".to_string()),
+                ("include.path".to_string(), "~/.config/git/personal".to_string()),
+                (
+                    "url.git@github.com:.insteadOf".to_string(),
+                    "https://github.com/".to_string()
+                ),
+            ]
+        );
+
+        let config: GitConfig =
+            parse_str(r#"gitconfig "~/.config/git/work" { "user.email" = "jane@work.com" }"#)
+                .unwrap();
+        assert_eq!(config.file.unwrap().value(), "~/.config/git/work");
+        assert_eq!(config.entries[0].key.value(), "user.email");
+    }
+
+    #[test]
+    fn rejects_invalid_keys() {
+        assert!(parse_str::<GitConfig>(r#"gitconfig { "name" = "Jane" }"#).is_err());
+        assert!(parse_str::<GitConfig>(r#"gitconfig { ".name" = "Jane" }"#).is_err());
+        assert!(parse_str::<GitConfig>(r#"gitconfig { "user." = "Jane" }"#).is_err());
+        assert!(parse_str::<GitConfig>(r#"gitconfig { user.name = "Jane" }"#).is_err());
+    }
+}
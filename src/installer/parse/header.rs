@@ -0,0 +1,73 @@
+use proc_macro2::Span;
+use syn::{parse::Parse, parse_quote, Attribute, Error, Ident, Token, Visibility};
+
+/// The optional `#[attrs] vis struct Name;` at the start of an installer, which defaults to `pub struct Installer;`
+pub struct Header {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub name: Ident,
+}
+
+impl Header {
+    pub fn peek(input: syn::parse::ParseStream) -> bool {
+        input.peek(Token![#]) || input.peek(Token![pub]) || input.peek(Token![struct])
+    }
+}
+
+impl Default for Header {
+    fn default() -> Self {
+        Self {
+            attrs: vec![],
+            vis: parse_quote!(pub),
+            name: Ident::new("Installer", Span::call_site()),
+        }
+    }
+}
+
+impl Parse for Header {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let struct_token: Option<Token![struct]> = input.parse()?;
+        if struct_token.is_none() {
+            return Err(Error::new(input.span(), "expected `struct`"));
+        }
+        let name = input.parse()?;
+        input.parse::<Token![;]>()?;
+
+        Ok(Self { attrs, vis, name })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use syn::parse_str;
+
+    use super::*;
+
+    #[test]
+    fn parses_header() {
+        let header: Header = parse_str(
+            r#"
+            /// The installer for laptops
+            #[allow(dead_code)]
+            pub(crate) struct LaptopInstaller;
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(header.attrs.len(), 2);
+        assert!(header.attrs[0].path.is_ident("doc"));
+        assert!(header.attrs[1].path.is_ident("allow"));
+        assert_eq!(header.vis.to_token_stream().to_string(), "pub (crate)");
+        assert_eq!(header.name, "LaptopInstaller");
+
+        let header: Header = parse_str("struct Private;").unwrap();
+        assert!(matches!(header.vis, Visibility::Inherited));
+        assert_eq!(header.name, "Private");
+
+        assert!(parse_str::<Header>("pub Installer;").is_err());
+        assert!(parse_str::<Header>("pub struct Installer").is_err());
+    }
+}
@@ -1,12 +1,50 @@
+use std::collections::HashSet;
+
 use syn::{parse::Parse, punctuated::Punctuated, Error, Token};
 
-use self::{cargo::Cargo, ensure::Ensure, package::Packages, script::Script, symlinks::Symlinks};
+use self::{
+    cargo::Cargo,
+    cron::Cron,
+    ensure::Ensure,
+    extract::Extract,
+    file::InstallerFile,
+    fonts::Fonts,
+    gitconfig::GitConfig,
+    header::Header,
+    package::{check_duplicate_packages, Packages},
+    repositories::Repositories,
+    rustup::Rustup,
+    script::Script,
+    secrets::Secrets,
+    services::Services,
+    shell::{Aliases, Functions},
+    ssh::Ssh,
+    step::{order_steps, Step},
+    symlinks::Symlinks,
+    tools::{Go, Npm, Pipx},
+};
 
 pub mod cargo;
+pub mod cron;
+pub mod derive;
 pub mod ensure;
+pub mod extract;
+pub mod file;
+pub mod fonts;
+pub mod gitconfig;
+pub mod header;
 pub mod package;
+pub mod repositories;
+pub mod rustup;
 pub mod script;
+pub mod secrets;
+pub mod services;
+pub mod shell;
+pub mod ssh;
+pub mod step;
 pub mod symlinks;
+pub mod toml;
+pub mod tools;
 
 mod kw {
     use syn::custom_keyword;
@@ -14,30 +52,142 @@ mod kw {
     custom_keyword!(cargo);
     custom_keyword!(ensure);
     custom_keyword!(packages);
+    custom_keyword!(repositories);
     custom_keyword!(symlinks);
     custom_keyword!(pacman);
     custom_keyword!(apt);
     custom_keyword!(brew);
+    custom_keyword!(brew_cask);
+    custom_keyword!(flatpak);
+    custom_keyword!(snap);
+    custom_keyword!(pacman_aur);
     custom_keyword!(exec);
+    custom_keyword!(optional);
+    custom_keyword!(pipx);
+    custom_keyword!(npm);
+    custom_keyword!(go);
+    custom_keyword!(rustup);
+    custom_keyword!(services);
+    custom_keyword!(user);
+    custom_keyword!(system);
+    custom_keyword!(from);
+    custom_keyword!(gitconfig);
+    custom_keyword!(ssh);
+    custom_keyword!(key);
+    custom_keyword!(host);
+    custom_keyword!(comment);
+    custom_keyword!(cron);
+    custom_keyword!(fonts);
+    custom_keyword!(download);
+    custom_keyword!(sha256);
+    custom_keyword!(extract);
+    custom_keyword!(strip);
+    custom_keyword!(secrets);
+    custom_keyword!(identity);
+    custom_keyword!(mode);
+    custom_keyword!(aliases);
+    custom_keyword!(functions);
+    custom_keyword!(file);
+    custom_keyword!(step);
+    custom_keyword!(tags);
+    custom_keyword!(after);
+    custom_keyword!(requires);
+    custom_keyword!(uninstall);
 }
 
+/// The keywords that start each kind of section, which are also the attributes accepted by `#[derive(Installer)]`
+pub const SECTION_KEYWORDS: &[&str] = &[
+    "aliases",
+    "cargo",
+    "cron",
+    "ensure",
+    "exec",
+    "extract",
+    "fonts",
+    "functions",
+    "gitconfig",
+    "go",
+    "npm",
+    "packages",
+    "pipx",
+    "repositories",
+    "rustup",
+    "secrets",
+    "services",
+    "ssh",
+    "symlinks",
+];
+
 pub struct Installer {
+    pub header: Header,
     pub sections: Vec<Section>,
 }
 
 impl Parse for Installer {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let header = if Header::peek(input) {
+            input.parse()?
+        } else {
+            Header::default()
+        };
         let sections = Punctuated::<Section, Token![;]>::parse_terminated(input)?;
-        let sections = sections.into_iter().collect();
-        Ok(Self { sections })
+
+        let mut step_set = HashSet::new();
+
+        for section in &sections {
+            if let Section::Step(step) = section {
+                if !step_set.insert(step.name.value()) {
+                    return Err(Error::new(
+                        step.name.span(),
+                        format!("Duplicate step `{}`", step.name.value()),
+                    ));
+                }
+            }
+        }
+
+        check_duplicate_packages(&sections)?;
+
+        let sections = order_steps(sections.into_iter().collect())?;
+        Ok(Self { header, sections })
+    }
+}
+
+/// The input to `installer!`, either the installer itself or a file to load it from
+pub enum InstallerInput {
+    Inline(Installer),
+    File(InstallerFile),
+}
+
+impl Parse for InstallerInput {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if InstallerFile::peek(input) {
+            Ok(Self::File(input.parse()?))
+        } else {
+            Ok(Self::Inline(input.parse()?))
+        }
     }
 }
 
 pub enum Section {
+    Aliases(Aliases),
     Cargo(Cargo),
+    Cron(Cron),
     Ensure(Ensure),
+    Extract(Extract),
+    Fonts(Fonts),
+    Functions(Functions),
+    GitConfig(GitConfig),
+    Go(Go),
+    Npm(Npm),
     Packages(Packages),
+    Pipx(Pipx),
+    Repositories(Repositories),
+    Rustup(Rustup),
     Script(Script),
+    Secrets(Secrets),
+    Services(Services),
+    Ssh(Ssh),
+    Step(Step),
     Symlinks(Symlinks),
 }
 
@@ -64,6 +214,13 @@ impl Section {
         }
     }
 
+    fn as_repositories(&self) -> Option<&Repositories> {
+        match self {
+            Self::Repositories(c) => Some(c),
+            _ => None,
+        }
+    }
+
     fn as_script(&self) -> Option<&Script> {
         match self {
             Self::Script(c) => Some(c),
@@ -81,14 +238,44 @@ impl Section {
 
 impl Parse for Section {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        if input.peek(kw::cargo) {
+        if input.peek(kw::aliases) {
+            Ok(Self::Aliases(input.parse()?))
+        } else if input.peek(kw::cargo) {
             Ok(Self::Cargo(input.parse()?))
+        } else if input.peek(kw::cron) {
+            Ok(Self::Cron(input.parse()?))
         } else if input.peek(kw::ensure) {
             Ok(Self::Ensure(input.parse()?))
         } else if input.peek(kw::exec) {
             Ok(Self::Script(input.parse()?))
+        } else if input.peek(kw::extract) {
+            Ok(Self::Extract(input.parse()?))
+        } else if input.peek(kw::fonts) {
+            Ok(Self::Fonts(input.parse()?))
+        } else if input.peek(kw::functions) {
+            Ok(Self::Functions(input.parse()?))
+        } else if input.peek(kw::gitconfig) {
+            Ok(Self::GitConfig(input.parse()?))
+        } else if input.peek(kw::go) {
+            Ok(Self::Go(input.parse()?))
+        } else if input.peek(kw::npm) {
+            Ok(Self::Npm(input.parse()?))
         } else if input.peek(kw::packages) {
             Ok(Self::Packages(input.parse()?))
+        } else if input.peek(kw::pipx) {
+            Ok(Self::Pipx(input.parse()?))
+        } else if input.peek(kw::repositories) {
+            Ok(Self::Repositories(input.parse()?))
+        } else if input.peek(kw::rustup) {
+            Ok(Self::Rustup(input.parse()?))
+        } else if input.peek(kw::secrets) {
+            Ok(Self::Secrets(input.parse()?))
+        } else if input.peek(kw::services) {
+            Ok(Self::Services(input.parse()?))
+        } else if input.peek(kw::ssh) {
+            Ok(Self::Ssh(input.parse()?))
+        } else if input.peek(kw::step) {
+            Ok(Self::Step(input.parse()?))
         } else if input.peek(kw::symlinks) {
             Ok(Self::Symlinks(input.parse()?))
         } else {
@@ -99,8 +286,6 @@ impl Parse for Section {
 
 #[cfg(test)]
 mod tests {
-    use std::ffi::OsString;
-
     use syn::parse_str;
 
     use crate::installer::parse::package::Package;
@@ -144,18 +329,12 @@ mod tests {
 
         let ensure = installer.sections.remove(0);
         let ensure = ensure.as_ensure().unwrap();
-        let home_paths = ensure
-            .home_paths
-            .iter()
-            .map(|s| s.value())
-            .collect::<Vec<_>>();
-        assert_eq!(home_paths, ["~/.local/bin"]);
-        let absolute_paths = ensure
-            .absolute_paths
+        let paths = ensure
+            .paths
             .iter()
             .map(|s| s.value())
             .collect::<Vec<_>>();
-        assert_eq!(absolute_paths, ["/foo/bar"]);
+        assert_eq!(paths, ["~/.local/bin", "/foo/bar"]);
 
         let script = installer.sections.remove(0);
         assert_eq!(
@@ -168,13 +347,128 @@ mod tests {
 
         let unzip = &packages.packages[0];
         assert!(
-            matches!(unzip, Package { name, pacman: None, apt: None, brew: None  } if name.value() == "unzip" )
+            matches!(unzip, Package { name, pacman: None, apt: None, brew: None, brew_cask: None, flatpak: None, snap: None, optional: false } if name.value() == "unzip" )
         );
 
-        let Package { name, pacman, apt, brew } = &packages.packages[1];
+        let Package { name, pacman, apt, brew, optional, .. } = &packages.packages[1];
         assert_eq!(name.value(), "build-essential");
         assert_eq!(pacman.as_ref().unwrap().value(), "base-devel");
         assert!(apt.is_none());
         assert!(brew.is_none());
+        assert!(!optional);
+    }
+
+    #[test]
+    fn parses_repositories_in_installer() {
+        let mut installer: Installer = parse_str(
+            r#"
+            repositories {
+                apt = ["ppa:neovim-ppa/unstable"],
+                pacman_aur = "paru",
+            };
+            packages { "neovim" };
+            "#,
+        )
+        .unwrap();
+
+        let repositories = installer.sections.remove(0);
+        let repositories = repositories.as_repositories().unwrap();
+        assert_eq!(repositories.apt[0].value(), "ppa:neovim-ppa/unstable");
+        assert!(repositories.brew.is_empty());
+        assert_eq!(repositories.pacman_aur.as_ref().unwrap().value(), "paru");
+
+        let packages = installer.sections.remove(0);
+        assert_eq!(packages.as_packages().unwrap().packages[0].name.value(), "neovim");
+    }
+
+    #[test]
+    fn parses_installer_with_header() {
+        let installer: Installer = parse_str(
+            r#"
+            /// Only what a server needs
+            pub(crate) struct ServerInstaller;
+
+            cargo { "ripgrep" };
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(installer.header.name, "ServerInstaller");
+        assert_eq!(installer.header.attrs.len(), 1);
+        assert_eq!(installer.sections.len(), 1);
+
+        let installer: Installer = parse_str(r#"cargo { "ripgrep" };"#).unwrap();
+        assert_eq!(installer.header.name, "Installer");
+        assert!(installer.header.attrs.is_empty());
+    }
+
+    #[test]
+    fn orders_steps_by_dependencies() {
+        let installer: Installer = parse_str(
+            r#"
+            step "cargo" requires "rustup" { cargo { "ripgrep" } };
+            exec "./setup.sh";
+            step "rustup" after "packages" { rustup { default = "stable" } };
+            step "packages" { packages { "build-essential" } };
+            "#,
+        )
+        .unwrap();
+
+        let order: Vec<_> = installer
+            .sections
+            .iter()
+            .map(|s| match s {
+                Section::Step(step) => step.name.value(),
+                _ => "exec".to_string(),
+            })
+            .collect();
+        assert_eq!(order, ["packages", "exec", "rustup", "cargo"]);
+    }
+
+    #[test]
+    fn rejects_bad_step_dependencies() {
+        let error = parse_str::<Installer>(
+            r#"
+            step "a" after "b" { };
+            step "b" requires "c" { };
+            step "c" after "a" { };
+            "#,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(error.to_string(), "Cycle between steps: `a` -> `b` -> `c` -> `a`");
+
+        let error = parse_str::<Installer>(r#"step "a" after "a" { }"#).err().unwrap();
+        assert_eq!(error.to_string(), "Cycle between steps: `a` -> `a`");
+
+        let error = parse_str::<Installer>(r#"step "a" after "nope" { }"#).err().unwrap();
+        assert_eq!(error.to_string(), "Unknown step `nope`");
+    }
+
+    #[test]
+    fn rejects_duplicate_steps() {
+        assert!(parse_str::<Installer>(
+            r#"
+            step "tools" { cargo { "ripgrep" } };
+            step "tools" tags(gui) { exec "./install_fonts.sh" };
+            "#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn rejects_conflicting_duplicate_packages() {
+        assert!(parse_str::<Installer>(r#"packages { "foo" }; exec "./a.sh"; packages { "foo" };"#).is_ok());
+
+        let error = parse_str::<Installer>(r#"packages { "foo"? }; packages { "foo" };"#).err().unwrap();
+        assert_eq!(error.to_string(), "Duplicate package `foo` with different options");
+
+        assert!(parse_str::<Installer>(
+            r#"
+            packages { "foo" };
+            step "apps" { packages { "foo" => { apt = "foo-bin" } } };
+            "#
+        )
+        .is_err());
     }
 }
@@ -1,17 +1,33 @@
-use std::fmt::Debug;
+use std::{collections::HashMap, fmt::Debug};
 
-use syn::{braced, parse::Parse, punctuated::Punctuated, spanned::Spanned, Error, LitStr, Token};
+use syn::{
+    braced, parenthesized, parse::Parse, punctuated::Punctuated, spanned::Spanned, Error, LitBool, LitStr,
+    Token,
+};
 
-use super::kw;
+use super::{kw, step::Step, Section};
 
 pub struct Packages {
     pub packages_kw: kw::packages,
+    /// Whether `uninstall` removes these packages, set with `packages (uninstall = true) { .. }`
+    pub uninstall: bool,
     pub packages: Vec<Package>,
 }
 
 impl Parse for Packages {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let packages_kw = input.parse()?;
+
+        let mut uninstall = false;
+        if input.peek(syn::token::Paren) {
+            let content;
+            let _ = parenthesized!(content in input);
+            content.parse::<kw::uninstall>()?;
+            content.parse::<Token![=]>()?;
+            uninstall = content.parse::<LitBool>()?.value;
+            let _: Option<Token![,]> = content.parse()?;
+        }
+
         let contents;
         let _ = braced!(contents in input);
 
@@ -20,6 +36,7 @@ impl Parse for Packages {
 
         Ok(Self {
             packages,
+            uninstall,
             packages_kw,
         })
     }
@@ -30,11 +47,17 @@ pub struct Package {
     pub pacman: Option<LitStr>,
     pub apt: Option<LitStr>,
     pub brew: Option<LitStr>,
+    pub brew_cask: Option<LitStr>,
+    pub flatpak: Option<LitStr>,
+    pub snap: Option<LitStr>,
+    /// Whether a failure to install this package should only produce a warning, which the runtime collects through `Context` for the final report
+    pub optional: bool,
 }
 
 impl Parse for Package {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let name = input.parse()?;
+        let question: Option<Token![?]> = input.parse()?;
         let arrow: Option<Token![=>]> = input.parse()?;
 
         match arrow {
@@ -43,48 +66,109 @@ impl Parse for Package {
                 pacman: None,
                 apt: None,
                 brew: None,
+                brew_cask: None,
+                flatpak: None,
+                snap: None,
+                optional: question.is_some(),
             }),
             Some(_) => {
                 let content;
                 let _ = braced!(content in input);
-                let overrides = Punctuated::<Override, Token![,]>::parse_terminated(&content)?;
-                let overrides = Vec::from_iter(overrides);
-
-                let apt = match single(overrides.iter(), |o| o.is_apt()) {
-                    Ok(package) => Some(package.value.clone()),
-                    Err(None) => None,
-                    Err(Some(value)) => {
-                        return Err(Error::new(value.apt.span(), "multiple apt overrides"))
-                    }
-                };
-
-                let brew = match single(overrides.iter(), |o| o.is_brew()) {
-                    Ok(package) => Some(package.value.clone()),
-                    Err(None) => None,
-                    Err(Some(value)) => {
-                        return Err(Error::new(value.brew.span(), "multiple brew overrides"))
+                let options = Punctuated::<PackageOption, Token![,]>::parse_terminated(&content)?;
+
+                let mut overrides = vec![];
+                let mut optional: Option<LitBool> = None;
+                for option in options {
+                    match option {
+                        PackageOption::Override(o) => overrides.push(o),
+                        PackageOption::Optional(kw, _) if optional.is_some() => {
+                            return Err(Error::new(kw.span(), "multiple optional flags"))
+                        }
+                        PackageOption::Optional(kw, _) if question.is_some() => {
+                            return Err(Error::new(
+                                kw.span(),
+                                "package is already marked optional with `?`",
+                            ))
+                        }
+                        PackageOption::Optional(_, value) => optional = Some(value),
                     }
-                };
-
-                let pacman = match single(overrides.iter(), |o| o.is_pacman()) {
-                    Ok(package) => Some(package.value.clone()),
-                    Err(None) => None,
-                    Err(Some(value)) => {
-                        return Err(Error::new(value.pacman.span(), "multiple pacman overrides"))
-                    }
-                };
+                }
+
+                let apt = single_override(&overrides, Override::is_apt, "apt")?;
+                let brew = single_override(&overrides, Override::is_brew, "brew")?;
+                let pacman = single_override(&overrides, Override::is_pacman, "pacman")?;
+                let brew_cask = single_override(&overrides, Override::is_brew_cask, "brew_cask")?;
+                let flatpak = single_override(&overrides, Override::is_flatpak, "flatpak")?;
+                let snap = single_override(&overrides, Override::is_snap, "snap")?;
+
+                if let (Some(_), Some(cask)) = (&brew, &brew_cask) {
+                    return Err(Error::new(
+                        cask.span(),
+                        "a package can't be both a brew formula and a brew cask",
+                    ));
+                }
 
                 Ok(Package {
                     name,
                     pacman,
                     apt,
                     brew,
+                    brew_cask,
+                    flatpak,
+                    snap,
+                    optional: question.is_some() || optional.is_some_and(|b| b.value),
                 })
             }
         }
     }
 }
 
+impl Package {
+    /// Whether `other` is installed the same way, ignoring its name
+    fn same_options(&self, other: &Package) -> bool {
+        let value = |o: &Option<LitStr>| o.as_ref().map(LitStr::value);
+        [
+            (&self.pacman, &other.pacman),
+            (&self.apt, &other.apt),
+            (&self.brew, &other.brew),
+            (&self.brew_cask, &other.brew_cask),
+            (&self.flatpak, &other.flatpak),
+            (&self.snap, &other.snap),
+        ]
+        .into_iter()
+        .all(|(a, b)| value(a) == value(b))
+            && self.optional == other.optional
+    }
+}
+
+/// Checks that every package listed more than once in `sections`, including inside steps, is installed the same way each time
+pub fn check_duplicate_packages<'a>(sections: impl IntoIterator<Item = &'a Section>) -> syn::Result<()> {
+    fn check<'a>(sections: impl IntoIterator<Item = &'a Section>, seen: &mut HashMap<String, &'a Package>) -> syn::Result<()> {
+        for section in sections {
+            match section {
+                Section::Packages(Packages { packages, .. }) => {
+                    for package in packages {
+                        match seen.insert(package.name.value(), package) {
+                            Some(first) if !first.same_options(package) => {
+                                return Err(Error::new(
+                                    package.name.span(),
+                                    format!("Duplicate package `{}` with different options", package.name.value()),
+                                ))
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Section::Step(Step { sections, .. }) => check(sections, seen)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    check(sections, &mut HashMap::new())
+}
+
 /// Given an iterator and a predicate, returns Ok(value) if it contains exactly 1 element, Err(None) if it contains 0, and Err(Some(value)) if it contains 2 or more
 fn single<I, T, F>(iter: I, mut pred: F) -> Result<T, Option<T>>
 where
@@ -103,10 +187,43 @@ where
     found.ok_or(None)
 }
 
+/// Finds the value of the single override matching `pred`, returning an error if there is more than one
+fn single_override(
+    overrides: &[Override],
+    pred: fn(&Override) -> bool,
+    name: &str,
+) -> syn::Result<Option<LitStr>> {
+    match single(overrides.iter(), |o| pred(o)) {
+        Ok(o) => Ok(Some(o.value.clone())),
+        Err(None) => Ok(None),
+        Err(Some(o)) => Err(Error::new(o.key_span(), format!("multiple {name} overrides"))),
+    }
+}
+
+enum PackageOption {
+    Override(Override),
+    Optional(kw::optional, LitBool),
+}
+
+impl Parse for PackageOption {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(kw::optional) {
+            let optional = input.parse()?;
+            input.parse::<Token![=]>()?;
+            Ok(Self::Optional(optional, input.parse()?))
+        } else {
+            Ok(Self::Override(input.parse()?))
+        }
+    }
+}
+
 struct Override {
     pacman: Option<kw::pacman>,
     apt: Option<kw::apt>,
     brew: Option<kw::brew>,
+    brew_cask: Option<kw::brew_cask>,
+    flatpak: Option<kw::flatpak>,
+    snap: Option<kw::snap>,
     value: LitStr,
 }
 
@@ -120,6 +237,26 @@ impl Override {
     fn is_brew(&self) -> bool {
         self.brew.is_some()
     }
+    fn is_brew_cask(&self) -> bool {
+        self.brew_cask.is_some()
+    }
+    fn is_flatpak(&self) -> bool {
+        self.flatpak.is_some()
+    }
+    fn is_snap(&self) -> bool {
+        self.snap.is_some()
+    }
+
+    fn key_span(&self) -> proc_macro2::Span {
+        self.pacman
+            .map(|k| k.span)
+            .or(self.apt.map(|k| k.span))
+            .or(self.brew.map(|k| k.span))
+            .or(self.brew_cask.map(|k| k.span))
+            .or(self.flatpak.map(|k| k.span))
+            .or(self.snap.map(|k| k.span))
+            .unwrap_or_else(|| self.value.span())
+    }
 }
 
 impl Debug for Override {
@@ -133,6 +270,9 @@ impl Parse for Override {
         let mut apt = None;
         let mut brew = None;
         let mut pacman = None;
+        let mut brew_cask = None;
+        let mut flatpak = None;
+        let mut snap = None;
 
         if input.peek(kw::apt) {
             apt = Some(input.parse()?);
@@ -143,6 +283,15 @@ impl Parse for Override {
         if input.peek(kw::brew) {
             brew = Some(input.parse()?);
         }
+        if input.peek(kw::brew_cask) {
+            brew_cask = Some(input.parse()?);
+        }
+        if input.peek(kw::flatpak) {
+            flatpak = Some(input.parse()?);
+        }
+        if input.peek(kw::snap) {
+            snap = Some(input.parse()?);
+        }
 
         input.parse::<Token![=]>()?;
 
@@ -151,6 +300,9 @@ impl Parse for Override {
             apt,
             brew,
             pacman,
+            brew_cask,
+            flatpak,
+            snap,
         })
     }
 }
@@ -165,17 +317,32 @@ mod tests {
     fn correctly_parses_overrides() {
         let o: Override = parse_str(r#"brew = "asdf""#).unwrap();
         assert!(
-            matches!(o, Override { apt: None, brew: Some(_), pacman: None, value } if value.value() == "asdf")
+            matches!(o, Override { apt: None, brew: Some(_), pacman: None, brew_cask: None, flatpak: None, snap: None, value } if value.value() == "asdf")
         );
 
         let o: Override = parse_str(r#"pacman = "asdf""#).unwrap();
         assert!(
-            matches!(o, Override { apt: None, brew: None, pacman: Some(_), value } if value.value() == "asdf")
+            matches!(o, Override { apt: None, brew: None, pacman: Some(_), brew_cask: None, flatpak: None, snap: None, value } if value.value() == "asdf")
         );
 
         let o: Override = parse_str(r#"apt = "asdf""#).unwrap();
         assert!(
-            matches!(o, Override { apt: Some(_), brew: None, pacman: None, value } if value.value() == "asdf")
+            matches!(o, Override { apt: Some(_), brew: None, pacman: None, brew_cask: None, flatpak: None, snap: None, value } if value.value() == "asdf")
+        );
+
+        let o: Override = parse_str(r#"brew_cask = "asdf""#).unwrap();
+        assert!(
+            matches!(o, Override { apt: None, brew: None, pacman: None, brew_cask: Some(_), flatpak: None, snap: None, value } if value.value() == "asdf")
+        );
+
+        let o: Override = parse_str(r#"flatpak = "org.asdf.Asdf""#).unwrap();
+        assert!(
+            matches!(o, Override { apt: None, brew: None, pacman: None, brew_cask: None, flatpak: Some(_), snap: None, value } if value.value() == "org.asdf.Asdf")
+        );
+
+        let o: Override = parse_str(r#"snap = "asdf""#).unwrap();
+        assert!(
+            matches!(o, Override { apt: None, brew: None, pacman: None, brew_cask: None, flatpak: None, snap: Some(_), value } if value.value() == "asdf")
         );
 
         parse_str::<Override>(r#"asdf = "asdf""#).unwrap_err();
@@ -200,13 +367,37 @@ mod tests {
 
         let empty_package: Package = parse_str(r#""some_package""#).unwrap();
         assert!(
-            matches!(empty_package, Package { name, pacman: None, apt: None, brew: None} if name.value() == "some_package")
+            matches!(empty_package, Package { name, pacman: None, apt: None, brew: None, brew_cask: None, flatpak: None, snap: None, optional: false } if name.value() == "some_package")
         );
 
         let err = parse_str::<Package>(r#""some_package" => { apt = "foo", apt = "foo" }"#);
         assert!(err.is_err());
     }
 
+    #[test]
+    fn correctly_parses_application_package() {
+        let p: Package = parse_str(
+            r#"
+            "firefox" => {
+                brew_cask = "firefox",
+                flatpak = "org.mozilla.firefox",
+                snap = "firefox",
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(p.brew_cask.unwrap().value(), "firefox");
+        assert_eq!(p.flatpak.unwrap().value(), "org.mozilla.firefox");
+        assert_eq!(p.snap.unwrap().value(), "firefox");
+        assert!(p.brew.is_none());
+
+        let err = parse_str::<Package>(r#""firefox" => { brew = "firefox", brew_cask = "firefox" }"#);
+        assert!(err.is_err());
+
+        let err = parse_str::<Package>(r#""firefox" => { flatpak = "a", flatpak = "b" }"#);
+        assert!(err.is_err());
+    }
+
     #[test]
     fn correctly_parses_packages() {
         let p: Packages = parse_str(
@@ -231,7 +422,42 @@ mod tests {
 
         let second = &p.packages[1];
         assert!(
-            matches!(second, Package { name, pacman: None, apt: None, brew: None} if name.value() == "other_package")
+            matches!(second, Package { name, pacman: None, apt: None, brew: None, brew_cask: None, flatpak: None, snap: None, optional: false } if name.value() == "other_package")
         );
     }
+
+    #[test]
+    fn parses_uninstall_option() {
+        let p: Packages = parse_str(r#"packages (uninstall = true) { "foo" }"#).unwrap();
+        assert!(p.uninstall);
+        assert_eq!(p.packages[0].name.value(), "foo");
+
+        let p: Packages = parse_str(r#"packages { "foo" }"#).unwrap();
+        assert!(!p.uninstall);
+
+        assert!(parse_str::<Packages>(r#"packages (remove = true) { "foo" }"#).is_err());
+        assert!(parse_str::<Packages>(r#"packages (uninstall) { "foo" }"#).is_err());
+    }
+
+    #[test]
+    fn correctly_parses_optional_packages() {
+        let p: Package = parse_str(r#""nerd-fonts"?"#).unwrap();
+        assert_eq!(p.name.value(), "nerd-fonts");
+        assert!(p.optional);
+
+        let p: Package = parse_str(r#""foo" => { optional = true, apt = "foo-bin" }"#).unwrap();
+        assert!(p.optional);
+        assert_eq!(p.apt.unwrap().value(), "foo-bin");
+
+        let p: Package = parse_str(r#""foo"? => { brew = "foo-brew" }"#).unwrap();
+        assert!(p.optional);
+        assert_eq!(p.brew.unwrap().value(), "foo-brew");
+
+        let p: Package = parse_str(r#""foo" => { optional = false }"#).unwrap();
+        assert!(!p.optional);
+
+        assert!(parse_str::<Package>(r#""foo" => { optional = true, optional = true }"#).is_err());
+        assert!(parse_str::<Package>(r#""foo"? => { optional = true }"#).is_err());
+        assert!(parse_str::<Package>(r#""foo" => { optional = "yes" }"#).is_err());
+    }
 }
@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+
+use syn::{
+    braced, bracketed, parse::Parse, punctuated::Punctuated, spanned::Spanned, Error, LitStr,
+    Token,
+};
+
+use super::kw;
+
+pub struct Repositories {
+    pub repositories_kw: kw::repositories,
+    pub apt: Vec<LitStr>,
+    pub brew: Vec<LitStr>,
+    pub pacman_aur: Option<LitStr>,
+}
+
+impl Parse for Repositories {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let repositories_kw = input.parse()?;
+        let content;
+        let _ = braced!(content in input);
+
+        let entries = Punctuated::<Entry, Token![,]>::parse_terminated(&content)?;
+
+        let mut seen = HashSet::new();
+        let mut apt = vec![];
+        let mut brew = vec![];
+        let mut pacman_aur = None;
+
+        for entry in entries {
+            if !seen.insert(entry.key()) {
+                return Err(Error::new(
+                    entry.span(),
+                    format!("multiple {} entries", entry.key()),
+                ));
+            }
+
+            match entry {
+                Entry::Apt(_, repos) => apt = repos,
+                Entry::Brew(_, taps) => brew = taps,
+                Entry::PacmanAur(_, helper) => pacman_aur = Some(helper),
+            }
+        }
+
+        Ok(Self {
+            repositories_kw,
+            apt,
+            brew,
+            pacman_aur,
+        })
+    }
+}
+
+enum Entry {
+    Apt(kw::apt, Vec<LitStr>),
+    Brew(kw::brew, Vec<LitStr>),
+    PacmanAur(kw::pacman_aur, LitStr),
+}
+
+impl Entry {
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Apt(..) => "apt",
+            Self::Brew(..) => "brew",
+            Self::PacmanAur(..) => "pacman_aur",
+        }
+    }
+
+    fn span(&self) -> proc_macro2::Span {
+        match self {
+            Self::Apt(kw, _) => kw.span(),
+            Self::Brew(kw, _) => kw.span(),
+            Self::PacmanAur(kw, _) => kw.span(),
+        }
+    }
+}
+
+impl Parse for Entry {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(kw::apt) {
+            let kw = input.parse()?;
+            input.parse::<Token![=]>()?;
+            Ok(Self::Apt(kw, parse_list(input)?))
+        } else if input.peek(kw::brew) {
+            let kw = input.parse()?;
+            input.parse::<Token![=]>()?;
+            Ok(Self::Brew(kw, parse_list(input)?))
+        } else if input.peek(kw::pacman_aur) {
+            let kw = input.parse()?;
+            input.parse::<Token![=]>()?;
+            Ok(Self::PacmanAur(kw, input.parse()?))
+        } else {
+            Err(Error::new(
+                input.span(),
+                "expected one of `apt`, `brew` or `pacman_aur`",
+            ))
+        }
+    }
+}
+
+fn parse_list(input: syn::parse::ParseStream) -> syn::Result<Vec<LitStr>> {
+    let content;
+    let _ = bracketed!(content in input);
+    let items = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+    Ok(items.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+
+    #[test]
+    fn parses_repositories_section() {
+        let r: Repositories = parse_str(
+            r#"repositories {
+                apt = ["ppa:neovim-ppa/unstable", "ppa:git-core/ppa"],
+                brew = ["homebrew/cask-fonts"],
+                pacman_aur = "paru",
+            }"#,
+        )
+        .unwrap();
+
+        let apt: Vec<_> = r.apt.iter().map(|lit| lit.value()).collect();
+        assert_eq!(apt, ["ppa:neovim-ppa/unstable", "ppa:git-core/ppa"]);
+        let brew: Vec<_> = r.brew.iter().map(|lit| lit.value()).collect();
+        assert_eq!(brew, ["homebrew/cask-fonts"]);
+        assert_eq!(r.pacman_aur.unwrap().value(), "paru");
+
+        let empty: Repositories = parse_str("repositories { apt = [] }").unwrap();
+        assert!(empty.apt.is_empty());
+        assert!(empty.brew.is_empty());
+        assert!(empty.pacman_aur.is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_repositories() {
+        assert!(parse_str::<Repositories>(r#"repositories { apt = ["a"], apt = ["b"] }"#).is_err());
+        assert!(parse_str::<Repositories>(r#"repositories { pacman_aur = ["paru"] }"#).is_err());
+        assert!(parse_str::<Repositories>(r#"repositories { dnf = ["copr"] }"#).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+
+use syn::{braced, bracketed, parse::Parse, punctuated::Punctuated, Error, LitStr, Token};
+
+use super::kw;
+
+pub struct Rustup {
+    pub rustup_kw: kw::rustup,
+    pub default: Option<LitStr>,
+    pub toolchains: Vec<LitStr>,
+    pub components: Vec<LitStr>,
+    pub targets: Vec<LitStr>,
+}
+
+impl Parse for Rustup {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let rustup_kw = input.parse()?;
+        let content;
+        let _ = braced!(content in input);
+
+        let entries = Punctuated::<Entry, Token![,]>::parse_terminated(&content)?;
+
+        let mut seen = HashSet::new();
+        let mut default = None;
+        let mut toolchains = vec![];
+        let mut components = vec![];
+        let mut targets = vec![];
+
+        for Entry { key, value } in entries {
+            let name = key.to_string();
+            if !seen.insert(name.clone()) {
+                return Err(Error::new(key.span(), format!("multiple {name} entries")));
+            }
+
+            match (name.as_str(), value) {
+                ("default", Value::Single(toolchain)) => default = Some(toolchain),
+                ("toolchains", Value::List(list)) => toolchains = list,
+                ("components", Value::List(list)) => components = list,
+                ("targets", Value::List(list)) => targets = list,
+                ("default", Value::List(_)) => {
+                    return Err(Error::new(key.span(), "expected a single default toolchain"))
+                }
+                ("toolchains" | "components" | "targets", Value::Single(_)) => {
+                    return Err(Error::new(key.span(), format!("expected a list of {name}")))
+                }
+                _ => {
+                    return Err(Error::new(
+                        key.span(),
+                        "expected one of `default`, `toolchains`, `components` or `targets`",
+                    ))
+                }
+            }
+        }
+
+        Ok(Self {
+            rustup_kw,
+            default,
+            toolchains,
+            components,
+            targets,
+        })
+    }
+}
+
+struct Entry {
+    key: syn::Ident,
+    value: Value,
+}
+
+enum Value {
+    Single(LitStr),
+    List(Vec<LitStr>),
+}
+
+impl Parse for Entry {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let key = input.parse()?;
+        input.parse::<Token![=]>()?;
+
+        let value = if input.peek(syn::token::Bracket) {
+            let content;
+            let _ = bracketed!(content in input);
+            let items = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+            Value::List(items.into_iter().collect())
+        } else {
+            Value::Single(input.parse()?)
+        };
+
+        Ok(Self { key, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+
+    fn values(lits: &[LitStr]) -> Vec<String> {
+        lits.iter().map(|lit| lit.value()).collect()
+    }
+
+    #[test]
+    fn parses_rustup_section() {
+        let rustup: Rustup = parse_str(
+            r#"rustup {
+                default = "stable",
+                toolchains = ["nightly"],
+                components = ["rust-analyzer", "clippy"],
+                targets = ["wasm32-unknown-unknown"],
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(rustup.default.unwrap().value(), "stable");
+        assert_eq!(values(&rustup.toolchains), ["nightly"]);
+        assert_eq!(values(&rustup.components), ["rust-analyzer", "clippy"]);
+        assert_eq!(values(&rustup.targets), ["wasm32-unknown-unknown"]);
+
+        let empty: Rustup = parse_str("rustup {}").unwrap();
+        assert!(empty.default.is_none());
+        assert!(empty.toolchains.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_rustup_section() {
+        assert!(parse_str::<Rustup>(r#"rustup { default = ["stable"] }"#).is_err());
+        assert!(parse_str::<Rustup>(r#"rustup { targets = "wasm32-unknown-unknown" }"#).is_err());
+        assert!(parse_str::<Rustup>(r#"rustup { default = "stable", default = "beta" }"#).is_err());
+        assert!(parse_str::<Rustup>(r#"rustup { profile = "minimal" }"#).is_err());
+    }
+}
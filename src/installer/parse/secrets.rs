@@ -0,0 +1,164 @@
+use std::collections::HashSet;
+
+use syn::{
+    braced, parenthesized, parse::Parse, punctuated::Punctuated, Error, LitInt, LitStr, Token,
+};
+
+use super::kw;
+
+/// The mode secrets are written with if none is given
+const DEFAULT_MODE: u32 = 0o600;
+
+pub struct Secrets {
+    pub secrets_kw: kw::secrets,
+    /// The age identity file to decrypt with, or the runtime's default if not given
+    pub identity: Option<LitStr>,
+    pub secrets: Vec<Secret>,
+}
+
+impl Parse for Secrets {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let secrets_kw = input.parse()?;
+
+        let mut identity = None;
+        if input.peek(syn::token::Paren) {
+            let content;
+            let _ = parenthesized!(content in input);
+            content.parse::<kw::identity>()?;
+            content.parse::<Token![=]>()?;
+            identity = Some(content.parse()?);
+            let _: Option<Token![,]> = content.parse()?;
+        }
+
+        let content;
+        let _ = braced!(content in input);
+
+        let secrets = Punctuated::<Secret, Token![,]>::parse_terminated(&content)?;
+
+        let mut destination_set = HashSet::new();
+
+        for secret in &secrets {
+            if !destination_set.insert(secret.destination.value()) {
+                return Err(Error::new(
+                    secret.destination.span(),
+                    format!("Duplicate secret for `{}`", secret.destination.value()),
+                ));
+            }
+        }
+
+        let secrets = secrets.into_iter().collect();
+
+        Ok(Self {
+            secrets_kw,
+            identity,
+            secrets,
+        })
+    }
+}
+
+/// An age-encrypted file in the dotfiles repo. Only its path ends up in the binary, it is decrypted at install time
+pub struct Secret {
+    pub source: LitStr,
+    pub arrow: Token![=>],
+    pub destination: LitStr,
+    pub mode: u32,
+}
+
+impl Parse for Secret {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let source: LitStr = input.parse()?;
+        if !source.value().ends_with(".age") {
+            return Err(Error::new(
+                source.span(),
+                "secrets must be age-encrypted `.age` files",
+            ));
+        }
+
+        let arrow = input.parse()?;
+        let destination = input.parse()?;
+
+        let mut mode = DEFAULT_MODE;
+        if input.peek(syn::token::Paren) {
+            let content;
+            let _ = parenthesized!(content in input);
+            content.parse::<kw::mode>()?;
+            content.parse::<Token![=]>()?;
+            let lit: LitInt = content.parse()?;
+            mode = lit.base10_parse()?;
+            let _: Option<Token![,]> = content.parse()?;
+
+            if mode > 0o777 {
+                return Err(Error::new(lit.span(), "expected a permission mode like `0o600`"));
+            }
+            if mode & 0o007 != 0 {
+                return Err(Error::new(
+                    lit.span(),
+                    "secrets can't be accessible to other users",
+                ));
+            }
+        }
+
+        Ok(Self {
+            source,
+            arrow,
+            destination,
+            mode,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+
+    #[test]
+    fn parses_secrets_section() {
+        let secrets: Secrets = parse_str(
+            r#"secrets {
+                "secrets/netrc.age" => "~/.netrc" (mode = 0o600),
+                "secrets/aws.age" => "~/.aws/credentials",
+                "secrets/shared.age" => "~/.config/shared" (mode = 0o640),
+            }"#,
+        )
+        .unwrap();
+
+        assert!(secrets.identity.is_none());
+        let parsed: Vec<_> = secrets
+            .secrets
+            .iter()
+            .map(|s| (s.source.value(), s.destination.value(), s.mode))
+            .collect();
+        assert_eq!(
+            parsed,
+            [
+                ("secrets/netrc.age".to_string(), "~/.netrc".to_string(), 0o600),
+                (
+                    "secrets/aws.age".to_string(),
+                    "~/.aws/credentials".to_string(),
+                    0o600
+                ),
+                (
+                    "secrets/shared.age".to_string(),
+                    "~/.config/shared".to_string(),
+                    0o640
+                ),
+            ]
+        );
+
+        let secrets: Secrets = parse_str(
+            r#"secrets (identity = "~/.config/age/keys.txt") { "secrets/netrc.age" => "~/.netrc" }"#,
+        )
+        .unwrap();
+        assert_eq!(secrets.identity.unwrap().value(), "~/.config/age/keys.txt");
+    }
+
+    #[test]
+    fn rejects_unsafe_secrets() {
+        assert!(parse_str::<Secrets>(r#"secrets { "secrets/netrc" => "~/.netrc" }"#).is_err());
+        assert!(parse_str::<Secrets>(r#"secrets { "a.age" => "~/a" (mode = 0o644) }"#).is_err());
+        assert!(parse_str::<Secrets>(r#"secrets { "a.age" => "~/a" (mode = 0o1600) }"#).is_err());
+        assert!(parse_str::<Secrets>(r#"secrets { "a.age" => "~/a", "b.age" => "~/a" }"#).is_err());
+    }
+}
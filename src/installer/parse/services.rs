@@ -0,0 +1,164 @@
+use std::collections::HashSet;
+
+use syn::{braced, parse::Parse, punctuated::Punctuated, Error, Ident, LitStr, Token};
+
+use super::kw;
+
+pub struct Services {
+    pub services_kw: kw::services,
+    pub units: Vec<Unit>,
+}
+
+impl Parse for Services {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let services_kw = input.parse()?;
+        let content;
+        let _ = braced!(content in input);
+
+        let units = Punctuated::<Unit, Token![,]>::parse_terminated(&content)?;
+
+        let mut unit_set = HashSet::new();
+
+        for unit in &units {
+            if !unit_set.insert((unit.scope, unit.name.value())) {
+                return Err(Error::new(
+                    unit.name.span(),
+                    format!("Duplicate unit `{}`", unit.name.value()),
+                ));
+            }
+        }
+
+        let units = units.into_iter().collect();
+
+        Ok(Self { services_kw, units })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitScope {
+    User,
+    System,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitState {
+    Enabled,
+    Started,
+}
+
+pub struct Unit {
+    pub scope: UnitScope,
+    pub name: LitStr,
+    /// The unit file in the dotfiles repo to link into the systemd search path, if any
+    pub file: Option<LitStr>,
+    pub arrow: Token![=>],
+    pub state: UnitState,
+}
+
+impl Parse for Unit {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let scope = if input.peek(kw::user) {
+            input.parse::<kw::user>()?;
+            UnitScope::User
+        } else if input.peek(kw::system) {
+            input.parse::<kw::system>()?;
+            UnitScope::System
+        } else {
+            return Err(Error::new(input.span(), "expected `user` or `system`"));
+        };
+
+        let name: LitStr = input.parse()?;
+        if name.value().is_empty() || name.value().contains('/') {
+            return Err(Error::new(
+                name.span(),
+                "unit names can't be empty or contain `/`, use `from` to link a unit file",
+            ));
+        }
+
+        let file = if input.peek(kw::from) {
+            input.parse::<kw::from>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
+        let arrow = input.parse()?;
+
+        let state: Ident = input.parse()?;
+        let state = match state.to_string().as_str() {
+            "enabled" => UnitState::Enabled,
+            "started" => UnitState::Started,
+            _ => return Err(Error::new(state.span(), "expected `enabled` or `started`")),
+        };
+
+        Ok(Self {
+            scope,
+            name,
+            file,
+            arrow,
+            state,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+
+    #[test]
+    fn parses_services_section() {
+        let services: Services = parse_str(
+            r#"services {
+                user "syncthing" => enabled,
+                user "backup.timer" from "systemd/backup.timer" => started,
+                system "sshd" => enabled,
+            }"#,
+        )
+        .unwrap();
+
+        let units: Vec<_> = services
+            .units
+            .iter()
+            .map(|u| {
+                (
+                    u.scope,
+                    u.name.value(),
+                    u.file.as_ref().map(|f| f.value()),
+                    u.state,
+                )
+            })
+            .collect();
+        assert_eq!(
+            units,
+            [
+                (UnitScope::User, "syncthing".to_string(), None, UnitState::Enabled),
+                (
+                    UnitScope::User,
+                    "backup.timer".to_string(),
+                    Some("systemd/backup.timer".to_string()),
+                    UnitState::Started
+                ),
+                (UnitScope::System, "sshd".to_string(), None, UnitState::Enabled),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_units() {
+        assert!(parse_str::<Services>(r#"services { "syncthing" => enabled }"#).is_err());
+        assert!(parse_str::<Services>(r#"services { user "syncthing" => running }"#).is_err());
+        assert!(parse_str::<Services>(r#"services { user "systemd/foo.service" => enabled }"#).is_err());
+        assert!(parse_str::<Services>(
+            r#"services { user "syncthing" => enabled, user "syncthing" => started }"#
+        )
+        .is_err());
+
+        // the same unit name can be managed separately for the user and the system
+        assert!(parse_str::<Services>(
+            r#"services { user "syncthing" => enabled, system "syncthing" => enabled }"#
+        )
+        .is_ok());
+    }
+}
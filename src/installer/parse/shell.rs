@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+
+use syn::{braced, parse::Parse, punctuated::Punctuated, Error, Ident, LitStr, Token};
+
+use super::kw;
+
+pub struct Aliases {
+    pub aliases_kw: kw::aliases,
+    pub aliases: Vec<Snippet>,
+}
+
+impl Parse for Aliases {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let aliases_kw = input.parse()?;
+        let aliases = parse_snippets(input, "alias")?;
+        Ok(Self {
+            aliases_kw,
+            aliases,
+        })
+    }
+}
+
+pub struct Functions {
+    pub functions_kw: kw::functions,
+    pub functions: Vec<Snippet>,
+}
+
+impl Parse for Functions {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let functions_kw = input.parse()?;
+        let functions = parse_snippets(input, "function")?;
+        Ok(Self {
+            functions_kw,
+            functions,
+        })
+    }
+}
+
+fn parse_snippets(input: syn::parse::ParseStream, kind: &str) -> syn::Result<Vec<Snippet>> {
+    let content;
+    let _ = braced!(content in input);
+
+    let snippets = Punctuated::<Snippet, Token![,]>::parse_terminated(&content)?;
+
+    let mut name_set = HashSet::new();
+
+    for snippet in &snippets {
+        if !name_set.insert(snippet.name.value()) {
+            return Err(Error::new(
+                snippet.name.span(),
+                format!("Duplicate {kind} `{}`", snippet.name.value()),
+            ));
+        }
+    }
+
+    Ok(snippets.into_iter().collect())
+}
+
+/// A named alias or function. Names that aren't valid Rust identifiers can be written as strings
+pub struct Snippet {
+    pub name: LitStr,
+    pub eq: Token![=],
+    pub body: LitStr,
+}
+
+impl Parse for Snippet {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name = if input.peek(LitStr) {
+            input.parse()?
+        } else {
+            let ident: Ident = input.parse()?;
+            LitStr::new(&ident.to_string(), ident.span())
+        };
+
+        let value = name.value();
+        if value.is_empty()
+            || value
+                .chars()
+                .any(|c| c.is_whitespace() || "=\"'`$/\\;|&()<>{}".contains(c))
+        {
+            return Err(Error::new(
+                name.span(),
+                format!("`{value}` can't be used as a shell alias or function name"),
+            ));
+        }
+
+        let eq = input.parse()?;
+        let body = input.parse()?;
+
+        Ok(Self { name, eq, body })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+
+    fn pairs(snippets: &[Snippet]) -> Vec<(String, String)> {
+        snippets
+            .iter()
+            .map(|s| (s.name.value(), s.body.value()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_aliases_section() {
+        let aliases: Aliases =
+            parse_str(r#"aliases { ll = "exa -l", g = "git", ".." = "cd .." }"#).unwrap();
+        assert_eq!(
+            pairs(&aliases.aliases),
+            [
+                ("ll".to_string(), "exa -l".to_string()),
+                ("g".to_string(), "git".to_string()),
+                ("..".to_string(), "cd ..".to_string()),
+            ]
+        );
+
+        assert!(parse_str::<Aliases>(r#"aliases { g = "git", g = "grep" }"#).is_err());
+        assert!(parse_str::<Aliases>(r#"aliases { "g s" = "git status" }"#).is_err());
+    }
+
+    #[test]
+    fn parses_functions_section() {
+        let functions: Functions =
+            parse_str(r##"functions { mkcd = r#"mkdir -p "$1" && cd "$1""# }"##).unwrap();
+        assert_eq!(
+            pairs(&functions.functions),
+            [("mkcd".to_string(), r#"mkdir -p "$1" && cd "$1""#.to_string())]
+        );
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::HashSet;
+
+use syn::{
+    braced, parenthesized, parse::Parse, punctuated::Punctuated, Error, Ident, Lit, LitStr, Token,
+};
+
+use super::kw;
+
+pub struct Ssh {
+    pub ssh_kw: kw::ssh,
+    pub keys: Vec<SshKey>,
+    pub hosts: Vec<SshHost>,
+}
+
+impl Parse for Ssh {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let ssh_kw = input.parse()?;
+        let content;
+        let _ = braced!(content in input);
+
+        let entries = Punctuated::<Entry, Token![,]>::parse_terminated(&content)?;
+
+        let mut keys = vec![];
+        let mut hosts = vec![];
+        let mut key_set = HashSet::new();
+        let mut host_set = HashSet::new();
+
+        for entry in entries {
+            match entry {
+                Entry::Key(key) => {
+                    if !key_set.insert(key.path.value()) {
+                        return Err(Error::new(
+                            key.path.span(),
+                            format!("Duplicate ssh key `{}`", key.path.value()),
+                        ));
+                    }
+                    keys.push(key);
+                }
+                Entry::Host(host) => {
+                    if !host_set.insert(host.name.value()) {
+                        return Err(Error::new(
+                            host.name.span(),
+                            format!("Duplicate ssh host `{}`", host.name.value()),
+                        ));
+                    }
+                    hosts.push(host);
+                }
+            }
+        }
+
+        Ok(Self {
+            ssh_kw,
+            keys,
+            hosts,
+        })
+    }
+}
+
+enum Entry {
+    Key(SshKey),
+    Host(SshHost),
+}
+
+impl Parse for Entry {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(kw::key) {
+            Ok(Self::Key(input.parse()?))
+        } else if input.peek(kw::host) {
+            Ok(Self::Host(input.parse()?))
+        } else {
+            Err(Error::new(input.span(), "expected `key` or `host`"))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SshKeyType {
+    Ed25519,
+    Ecdsa,
+    Rsa,
+}
+
+/// A key pair that is generated if the private key doesn't exist yet
+pub struct SshKey {
+    pub key_kw: kw::key,
+    pub path: LitStr,
+    pub key_type: SshKeyType,
+    pub comment: Option<LitStr>,
+}
+
+impl Parse for SshKey {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let key_kw = input.parse()?;
+        let path = input.parse()?;
+
+        let mut key_type = None;
+        let mut comment = None;
+
+        if input.peek(syn::token::Paren) {
+            let content;
+            let _ = parenthesized!(content in input);
+
+            while !content.is_empty() {
+                if content.peek(Token![type]) {
+                    let type_kw: Token![type] = content.parse()?;
+                    content.parse::<Token![=]>()?;
+                    let ident: Ident = content.parse()?;
+                    let parsed = match ident.to_string().as_str() {
+                        "ed25519" => SshKeyType::Ed25519,
+                        "ecdsa" => SshKeyType::Ecdsa,
+                        "rsa" => SshKeyType::Rsa,
+                        _ => {
+                            return Err(Error::new(
+                                ident.span(),
+                                "expected one of `ed25519`, `ecdsa` or `rsa`",
+                            ))
+                        }
+                    };
+                    if key_type.replace(parsed).is_some() {
+                        return Err(Error::new(type_kw.span, "multiple key types"));
+                    }
+                } else if content.peek(kw::comment) {
+                    let comment_kw: kw::comment = content.parse()?;
+                    content.parse::<Token![=]>()?;
+                    if comment.replace(content.parse()?).is_some() {
+                        return Err(Error::new(comment_kw.span, "multiple comments"));
+                    }
+                } else {
+                    return Err(Error::new(content.span(), "expected `type` or `comment`"));
+                }
+
+                if !content.is_empty() {
+                    content.parse::<Token![,]>()?;
+                }
+            }
+        }
+
+        Ok(Self {
+            key_kw,
+            path,
+            key_type: key_type.unwrap_or(SshKeyType::Ed25519),
+            comment,
+        })
+    }
+}
+
+/// A `Host` block in `~/.ssh/config`
+pub struct SshHost {
+    pub host_kw: kw::host,
+    pub name: LitStr,
+    pub options: Vec<(Ident, LitStr)>,
+}
+
+impl Parse for SshHost {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let host_kw = input.parse()?;
+        let name = input.parse()?;
+        let content;
+        let _ = braced!(content in input);
+
+        let options = Punctuated::<HostOption, Token![,]>::parse_terminated(&content)?;
+        let options = options.into_iter().map(|o| (o.key, o.value)).collect();
+
+        Ok(Self {
+            host_kw,
+            name,
+            options,
+        })
+    }
+}
+
+struct HostOption {
+    key: Ident,
+    value: LitStr,
+}
+
+impl Parse for HostOption {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let key = input.parse()?;
+        input.parse::<Token![=]>()?;
+
+        // numbers are allowed for options like `Port = 2222`
+        let value = match input.parse()? {
+            Lit::Str(s) => s,
+            Lit::Int(i) => LitStr::new(&i.to_string(), i.span()),
+            lit => return Err(Error::new(lit.span(), "expected a string or an integer")),
+        };
+
+        Ok(Self { key, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+
+    #[test]
+    fn parses_ssh_section() {
+        let ssh: Ssh = parse_str(
+            r#"ssh {
+                key "~/.ssh/id_ed25519" (type = ed25519, comment = "{{hostname}}"),
+                key "~/.ssh/id_rsa_legacy" (type = rsa),
+                key "~/.ssh/id_default",
+                host "build" {
+                    HostName = "10.0.0.5",
+                    User = "ci",
+                    Port = 2222,
+                },
+            }"#,
+        )
+        .unwrap();
+
+        let keys: Vec<_> = ssh
+            .keys
+            .iter()
+            .map(|k| (k.path.value(), k.key_type, k.comment.as_ref().map(|c| c.value())))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                (
+                    "~/.ssh/id_ed25519".to_string(),
+                    SshKeyType::Ed25519,
+                    Some("{{hostname}}".to_string())
+                ),
+                ("~/.ssh/id_rsa_legacy".to_string(), SshKeyType::Rsa, None),
+                ("~/.ssh/id_default".to_string(), SshKeyType::Ed25519, None),
+            ]
+        );
+
+        let host = &ssh.hosts[0];
+        assert_eq!(host.name.value(), "build");
+        let options: Vec<_> = host
+            .options
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.value()))
+            .collect();
+        assert_eq!(
+            options,
+            [
+                ("HostName".to_string(), "10.0.0.5".to_string()),
+                ("User".to_string(), "ci".to_string()),
+                ("Port".to_string(), "2222".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_ssh_section() {
+        assert!(parse_str::<Ssh>(r#"ssh { key "~/.ssh/id" (type = dsa) }"#).is_err());
+        assert!(parse_str::<Ssh>(r#"ssh { key "~/.ssh/id" (type = rsa, type = ecdsa) }"#).is_err());
+        assert!(parse_str::<Ssh>(r#"ssh { key "~/.ssh/id" (bits = 4096) }"#).is_err());
+        assert!(parse_str::<Ssh>(r#"ssh { key "~/.ssh/id", key "~/.ssh/id" }"#).is_err());
+        assert!(parse_str::<Ssh>(r#"ssh { host "a" {}, host "a" {} }"#).is_err());
+        assert!(parse_str::<Ssh>(r#"ssh { host "a" { ForwardAgent = true } }"#).is_err());
+        assert!(parse_str::<Ssh>(r#"ssh { agent "a" }"#).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::{HashMap, HashSet};
+
+use syn::{braced, parenthesized, parse::Parse, punctuated::Punctuated, Error, Ident, LitStr, Token};
+
+use super::{kw, Section};
+
+/// A named group of sections, e.g. `step "fonts" tags(gui, slow) { exec "./install_fonts.sh" }`, which can be selected with `install_only`
+pub struct Step {
+    pub step_kw: kw::step,
+    pub name: LitStr,
+    pub tags: Vec<Ident>,
+    /// Steps that must come before this one if they are installed
+    pub after: Vec<LitStr>,
+    /// Steps that must come before this one, and that have to succeed for it to run
+    pub requires: Vec<LitStr>,
+    pub sections: Vec<Section>,
+}
+
+impl Parse for Step {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let step_kw = input.parse()?;
+        let name: LitStr = input.parse()?;
+
+        if name.value().is_empty() {
+            return Err(Error::new(name.span(), "Step names can't be empty"));
+        }
+
+        let mut tags = vec![];
+        let mut after = vec![];
+        let mut requires = vec![];
+
+        loop {
+            if input.peek(kw::tags) {
+                input.parse::<kw::tags>()?;
+                let content;
+                let _ = parenthesized!(content in input);
+
+                let mut tag_set: HashSet<_> = tags.iter().map(Ident::to_string).collect();
+                for tag in Punctuated::<Ident, Token![,]>::parse_terminated(&content)? {
+                    if !tag_set.insert(tag.to_string()) {
+                        return Err(Error::new(tag.span(), format!("Duplicate tag `{tag}`")));
+                    }
+                    tags.push(tag);
+                }
+            } else if input.peek(kw::after) {
+                input.parse::<kw::after>()?;
+                after.extend(parse_step_names(input)?);
+            } else if input.peek(kw::requires) {
+                input.parse::<kw::requires>()?;
+                requires.extend(parse_step_names(input)?);
+            } else {
+                break;
+            }
+        }
+
+        let content;
+        let _ = braced!(content in input);
+        let sections = Punctuated::<Section, Token![;]>::parse_terminated(&content)?;
+
+        if let Some(step) = sections.iter().find_map(|s| match s {
+            Section::Step(step) => Some(step),
+            _ => None,
+        }) {
+            return Err(Error::new(step.name.span(), "Steps can't be nested"));
+        }
+
+        Ok(Self {
+            step_kw,
+            name,
+            tags,
+            after,
+            requires,
+            sections: sections.into_iter().collect(),
+        })
+    }
+}
+
+/// Parses `"a", "b"`, the names following `after` or `requires`
+fn parse_step_names(input: syn::parse::ParseStream) -> syn::Result<Vec<LitStr>> {
+    let mut names = vec![input.parse()?];
+    while input.peek(Token![,]) {
+        input.parse::<Token![,]>()?;
+        names.push(input.parse()?);
+    }
+    Ok(names)
+}
+
+/// Reorders the steps in `sections` so every step comes after the steps it depends on, otherwise keeping the order they were written in.
+/// Only the steps move, between the positions steps were written at, so other sections stay where they were written
+pub fn order_steps(sections: Vec<Section>) -> syn::Result<Vec<Section>> {
+    let indices: HashMap<_, _> = sections
+        .iter()
+        .enumerate()
+        .filter_map(|(i, s)| match s {
+            Section::Step(step) => Some((step.name.value(), i)),
+            _ => None,
+        })
+        .collect();
+
+    let mut dependencies = vec![vec![]; sections.len()];
+    for (i, section) in sections.iter().enumerate() {
+        if let Section::Step(step) = section {
+            for name in step.after.iter().chain(&step.requires) {
+                match indices.get(&name.value()) {
+                    Some(&j) => dependencies[i].push((j, name)),
+                    None => {
+                        return Err(Error::new(
+                            name.span(),
+                            format!("Unknown step `{}`", name.value()),
+                        ))
+                    }
+                }
+            }
+        }
+    }
+
+    // each time, the first step whose dependencies have all been placed is placed next
+    let steps: Vec<_> = (0..sections.len())
+        .filter(|&i| matches!(sections[i], Section::Step(_)))
+        .collect();
+    let mut placed = vec![false; sections.len()];
+    let mut order = vec![];
+    while let Some(&next) = steps
+        .iter()
+        .find(|&&i| !placed[i] && dependencies[i].iter().all(|(j, _)| placed[*j]))
+    {
+        placed[next] = true;
+        order.push(next);
+    }
+
+    if order.len() < steps.len() {
+        // anything left over is part of a cycle or waiting on one, so follow unplaced dependencies until a step repeats
+        let mut path = vec![*steps.iter().find(|&&i| !placed[i]).unwrap()];
+        loop {
+            let current = *path.last().unwrap();
+            let (next, name) = dependencies[current]
+                .iter()
+                .find(|(j, _)| !placed[*j])
+                .unwrap();
+
+            if let Some(start) = path.iter().position(|i| i == next) {
+                let mut cycle: Vec<_> = path[start..]
+                    .iter()
+                    .map(|i| match &sections[*i] {
+                        Section::Step(step) => format!("`{}`", step.name.value()),
+                        _ => unreachable!("only steps have dependencies"),
+                    })
+                    .collect();
+                cycle.push(format!("`{}`", name.value()));
+                return Err(Error::new(
+                    name.span(),
+                    format!("Cycle between steps: {}", cycle.join(" -> ")),
+                ));
+            }
+            path.push(*next);
+        }
+    }
+
+    // the nth step position gets the nth step in `order`
+    let mut order = order.into_iter();
+    let positions: Vec<_> = (0..sections.len())
+        .map(|i| match &sections[i] {
+            Section::Step(_) => order.next().unwrap(),
+            _ => i,
+        })
+        .collect();
+
+    let mut sections: Vec<_> = sections.into_iter().map(Some).collect();
+    Ok(positions
+        .into_iter()
+        .map(|i| sections[i].take().unwrap())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+
+    #[test]
+    fn parses_step() {
+        let step: Step = parse_str(
+            r#"step "fonts" tags(gui, slow) {
+                exec "./install_fonts.sh";
+                fonts { "fonts/*.ttf" };
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(step.name.value(), "fonts");
+        assert_eq!(step.tags.len(), 2);
+        assert_eq!(step.tags[1], "slow");
+        assert_eq!(step.sections.len(), 2);
+
+        let step: Step = parse_str(r#"step "tools" { cargo { "ripgrep" } }"#).unwrap();
+        assert!(step.tags.is_empty());
+
+        assert!(parse_str::<Step>(r#"step "a" tags(gui, gui) { }"#).is_err());
+        assert!(parse_str::<Step>(r#"step "a" { step "b" { } }"#).is_err());
+        assert!(parse_str::<Step>(r#"step "" { }"#).is_err());
+    }
+
+    #[test]
+    fn parses_step_dependencies() {
+        let step: Step = parse_str(
+            r#"step "cargo" after "rustup" requires "packages", "repositories" { cargo { "ripgrep" } }"#,
+        )
+        .unwrap();
+
+        let names = |lits: &[LitStr]| lits.iter().map(LitStr::value).collect::<Vec<_>>();
+        assert_eq!(names(&step.after), ["rustup"]);
+        assert_eq!(names(&step.requires), ["packages", "repositories"]);
+    }
+}
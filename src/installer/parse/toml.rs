@@ -0,0 +1,791 @@
+use std::{ops::Range, path::PathBuf};
+
+use syn::{parse::Parse, LitStr};
+use toml_edit::{ImDocument, Item, TableLike, Value};
+
+use super::{
+    file::{locate, parse_contents, read},
+    header::Header,
+    Installer,
+};
+
+/// The input to `installer_from_toml!`, an optional header followed by the path of a TOML file relative to `CARGO_MANIFEST_DIR`
+///
+/// Each top level table of the file is a section, for example:
+///
+/// ```toml
+/// [cargo]
+/// crates = ["ripgrep", "exa"]
+///
+/// [[symlinks]]
+/// link = "~/.bashrc"
+/// original = "config/bashrc"
+///
+/// [packages.build-essential]
+/// pacman = "base-devel"
+/// ```
+pub struct TomlInstaller {
+    pub header: Header,
+    pub path: LitStr,
+}
+
+impl Parse for TomlInstaller {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let header = if Header::peek(input) {
+            input.parse()?
+        } else {
+            Header::default()
+        };
+        let path = input.parse()?;
+
+        Ok(Self { header, path })
+    }
+}
+
+impl TomlInstaller {
+    /// Reads and parses the file, returning the installer along with the absolute path that was read
+    pub fn load(self) -> syn::Result<(Installer, PathBuf)> {
+        let (contents, path) = read(&self.path)?;
+
+        let errors = match parse_toml(&contents) {
+            Ok(mut installer) => {
+                installer.header = self.header;
+                return Ok((installer, path));
+            }
+            Err(errors) => errors,
+        };
+
+        let errors: Vec<_> = errors
+            .into_iter()
+            .map(|(span, message)| {
+                let (line, column) = line_column(&contents, span.start);
+                (line, column, message)
+            })
+            .collect();
+
+        Err(locate(&self.path, &errors))
+    }
+}
+
+/// An error message with the byte range in the TOML file it refers to
+type TomlError = (Range<usize>, String);
+
+/// A table from the TOML file along with its span
+type SpannedTable<'a> = (&'a dyn TableLike, Range<usize>);
+
+/// Converts `contents` into the installer syntax and parses that, so the TOML front-end gets exactly the same validation as `installer!`
+fn parse_toml(contents: &str) -> Result<Installer, Vec<TomlError>> {
+    let document = ImDocument::parse(contents).map_err(|e| {
+        let span = e.span().unwrap_or(0..0);
+        vec![(span, e.message().to_string())]
+    })?;
+
+    let mut renderer = Renderer::default();
+    renderer
+        .render(document.as_table(), &(0..contents.len()))
+        .map_err(|e| vec![e])?;
+
+    parse_contents(&renderer.source).map_err(|errors| {
+        errors
+            .into_iter()
+            .map(|(line, _, message)| (renderer.spans[line - 1].clone(), message))
+            .collect()
+    })
+}
+
+/// Returns the 1-based line and column of `offset` in `contents`
+fn line_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+    (line, column)
+}
+
+#[derive(Default)]
+struct Renderer {
+    source: String,
+    /// The span in the TOML file that each line of `source` was rendered from
+    spans: Vec<Range<usize>>,
+}
+
+impl Renderer {
+    fn line(&mut self, span: &Range<usize>, line: &str) {
+        self.source.push_str(line);
+        self.source.push('\n');
+        self.spans.push(span.clone());
+    }
+
+    fn render(&mut self, document: &dyn TableLike, span: &Range<usize>) -> Result<(), TomlError> {
+        for (name, key_span, item) in entries(document, span) {
+            let span = &item.span().unwrap_or(key_span.clone());
+
+            match name {
+                "cargo" => {
+                    let table = table_like(item, span, name)?;
+                    only(table, span, name, &["crates"])?;
+                    self.list(table, span, "cargo", "crates")?;
+                }
+                "ensure" => {
+                    let table = table_like(item, span, name)?;
+                    only(table, span, name, &["paths"])?;
+                    self.list(table, span, "ensure", "paths")?;
+                }
+                "exec" => {
+                    let table = table_like(item, span, name)?;
+                    only(table, span, name, &["scripts"])?;
+                    if let Some(scripts) = table.get("scripts") {
+                        for (script, script_span) in strings(scripts, span)? {
+                            self.line(&script_span, &format!("exec {};", lit(script)));
+                        }
+                    }
+                }
+                "packages" => self.packages(table_like(item, span, name)?, span)?,
+                "symlinks" => {
+                    self.line(span, "symlinks {");
+                    for (link, link_span) in tables(item, span, name)? {
+                        only(link, &link_span, name, &["link", "original"])?;
+                        let (from, from_span) = field(link, &link_span, "link")?;
+                        let (to, to_span) = field(link, &link_span, "original")?;
+                        self.line(&from_span, &format!("{} =>", lit(from)));
+                        self.line(&to_span, &format!("{},", lit(to)));
+                    }
+                    self.line(span, "};");
+                }
+                "repositories" => {
+                    let table = table_like(item, span, name)?;
+                    only(table, span, name, &["apt", "brew", "pacman_aur"])?;
+                    self.line(span, "repositories {");
+                    for (key, key_span, value) in entries(table, span) {
+                        let value_span = value.span().unwrap_or(key_span);
+                        match value.as_str() {
+                            Some(s) => self.line(&value_span, &format!("{key} = {},", lit(s))),
+                            None => {
+                                let list = strings(value, &value_span)?;
+                                self.line(&value_span, &format!("{key} = [{}],", lits(&list)));
+                            }
+                        }
+                    }
+                    self.line(span, "};");
+                }
+                "pipx" | "npm" | "go" => {
+                    // tools are pinned to a version, or `"*"` for whatever is latest
+                    self.line(span, &format!("{name} {{"));
+                    for (tool, key_span, version) in entries(table_like(item, span, name)?, span) {
+                        let version_span = version.span().unwrap_or(key_span);
+                        match string(version, &version_span)? {
+                            "*" => self.line(&version_span, &format!("{},", lit(tool))),
+                            v => self.line(&version_span, &format!("{} => {},", lit(tool), lit(v))),
+                        }
+                    }
+                    self.line(span, "};");
+                }
+                "rustup" => {
+                    let table = table_like(item, span, name)?;
+                    only(
+                        table,
+                        span,
+                        name,
+                        &["default", "toolchains", "components", "targets"],
+                    )?;
+                    self.line(span, "rustup {");
+                    for (key, key_span, value) in entries(table, span) {
+                        let value_span = value.span().unwrap_or(key_span);
+                        match value.as_str() {
+                            Some(s) => self.line(&value_span, &format!("{key} = {},", lit(s))),
+                            None => {
+                                let list = strings(value, &value_span)?;
+                                self.line(&value_span, &format!("{key} = [{}],", lits(&list)));
+                            }
+                        }
+                    }
+                    self.line(span, "};");
+                }
+                "services" => {
+                    self.line(span, "services {");
+                    for (unit, unit_span) in tables(item, span, name)? {
+                        only(unit, &unit_span, name, &["name", "scope", "file", "state"])?;
+                        let scope = match unit.get("scope") {
+                            Some(scope) => keyword(scope, &unit_span, &["user", "system"])?,
+                            None => "user",
+                        };
+                        let (unit_name, name_span) = field(unit, &unit_span, "name")?;
+                        self.line(&name_span, &format!("{scope} {}", lit(unit_name)));
+                        if unit.get("file").is_some() {
+                            let (file, file_span) = field(unit, &unit_span, "file")?;
+                            self.line(&file_span, &format!("from {}", lit(file)));
+                        }
+                        let state = required(unit, &unit_span, "state")?;
+                        let state_span = state.span().unwrap_or_else(|| unit_span.clone());
+                        let state = keyword(state, &unit_span, &["enabled", "started"])?;
+                        self.line(&state_span, &format!("=> {state},"));
+                    }
+                    self.line(span, "};");
+                }
+                "gitconfig" => {
+                    // nested tables are flattened, so `[gitconfig.user] name = "..."` sets `user.name`
+                    self.line(span, "gitconfig {");
+                    self.gitconfig(table_like(item, span, name)?, span, "")?;
+                    self.line(span, "};");
+                }
+                "ssh" => self.ssh(table_like(item, span, name)?, span)?,
+                "cron" => {
+                    self.line(span, "cron {");
+                    for (job, job_span) in tables(item, span, name)? {
+                        only(job, &job_span, name, &["schedule", "command"])?;
+                        let (schedule, schedule_span) = field(job, &job_span, "schedule")?;
+                        let (command, command_span) = field(job, &job_span, "command")?;
+                        self.line(&schedule_span, &format!("{} =>", lit(schedule)));
+                        self.line(&command_span, &format!("{},", lit(command)));
+                    }
+                    self.line(span, "};");
+                }
+                "fonts" => {
+                    let table = table_like(item, span, name)?;
+                    only(table, span, name, &["files", "downloads"])?;
+                    self.line(span, "fonts {");
+                    if let Some(files) = table.get("files") {
+                        for (file, file_span) in strings(files, span)? {
+                            self.line(&file_span, &format!("{},", lit(file)));
+                        }
+                    }
+                    if let Some(downloads) = table.get("downloads") {
+                        for (download, download_span) in tables(downloads, span, "fonts.downloads")?
+                        {
+                            only(
+                                download,
+                                &download_span,
+                                "fonts.downloads",
+                                &["url", "sha256"],
+                            )?;
+                            let (url, url_span) = field(download, &download_span, "url")?;
+                            let (sha256, sha256_span) =
+                                field(download, &download_span, "sha256")?;
+                            self.line(&url_span, &format!("download {}", lit(url)));
+                            self.line(&sha256_span, &format!("sha256 = {},", lit(sha256)));
+                        }
+                    }
+                    self.line(span, "};");
+                }
+                "extract" => {
+                    self.line(span, "extract {");
+                    for (archive, archive_span) in tables(item, span, name)? {
+                        only(
+                            archive,
+                            &archive_span,
+                            name,
+                            &["archive", "destination", "strip"],
+                        )?;
+                        let (from, from_span) = field(archive, &archive_span, "archive")?;
+                        let (to, to_span) = field(archive, &archive_span, "destination")?;
+                        self.line(&from_span, &format!("{} =>", lit(from)));
+                        self.line(&to_span, &lit(to));
+                        if let Some(strip) = archive.get("strip") {
+                            let strip_span = strip.span().unwrap_or_else(|| archive_span.clone());
+                            let strip = integer(strip, &archive_span)?;
+                            self.line(&strip_span, &format!("(strip = {strip})"));
+                        }
+                        self.line(&archive_span, ",");
+                    }
+                    self.line(span, "};");
+                }
+                "secrets" => {
+                    let table = table_like(item, span, name)?;
+                    only(table, span, name, &["identity", "files"])?;
+                    match table.get("identity") {
+                        Some(identity) => self.line(
+                            span,
+                            &format!("secrets (identity = {}) {{", lit(string(identity, span)?)),
+                        ),
+                        None => self.line(span, "secrets {"),
+                    }
+                    if let Some(files) = table.get("files") {
+                        for (secret, secret_span) in tables(files, span, "secrets.files")? {
+                            only(
+                                secret,
+                                &secret_span,
+                                "secrets.files",
+                                &["source", "destination", "mode"],
+                            )?;
+                            let (from, from_span) = field(secret, &secret_span, "source")?;
+                            let (to, to_span) = field(secret, &secret_span, "destination")?;
+                            self.line(&from_span, &format!("{} =>", lit(from)));
+                            self.line(&to_span, &lit(to));
+                            if let Some(mode) = secret.get("mode") {
+                                let mode_span = mode.span().unwrap_or_else(|| secret_span.clone());
+                                let mode = integer(mode, &secret_span)?;
+                                self.line(&mode_span, &format!("(mode = 0o{mode:o})"));
+                            }
+                            self.line(&secret_span, ",");
+                        }
+                    }
+                    self.line(span, "};");
+                }
+                "aliases" | "functions" => {
+                    self.line(span, &format!("{name} {{"));
+                    for (key, key_span, value) in entries(table_like(item, span, name)?, span) {
+                        let value_span = value.span().unwrap_or(key_span);
+                        let value = string(value, &value_span)?;
+                        self.line(&value_span, &format!("{} = {},", lit(key), lit(value)));
+                    }
+                    self.line(span, "};");
+                }
+                _ => return Err((key_span, format!("unknown section `{name}`"))),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Renders a section that is just a list of strings from `key` in `table`
+    fn list(
+        &mut self,
+        table: &dyn TableLike,
+        span: &Range<usize>,
+        keyword: &str,
+        key: &str,
+    ) -> Result<(), TomlError> {
+        self.line(span, &format!("{keyword} {{"));
+        if let Some(list) = table.get(key) {
+            for (item, item_span) in strings(list, span)? {
+                self.line(&item_span, &format!("{},", lit(item)));
+            }
+        }
+        self.line(span, "};");
+        Ok(())
+    }
+
+    fn packages(&mut self, table: &dyn TableLike, span: &Range<usize>) -> Result<(), TomlError> {
+        self.line(span, "packages {");
+        for (name, key_span, package) in entries(table, span) {
+            let package_span = package.span().unwrap_or(key_span);
+            match package {
+                // `unzip = true` is a package without any overrides
+                Item::Value(Value::Boolean(b)) if *b.value() => {
+                    self.line(&package_span, &format!("{},", lit(name)));
+                }
+                _ => {
+                    let overrides = table_like(package, &package_span, name)?;
+                    only(
+                        overrides,
+                        &package_span,
+                        name,
+                        &["pacman", "apt", "brew", "brew_cask", "flatpak", "snap", "optional"],
+                    )?;
+                    self.line(&package_span, &format!("{} => {{", lit(name)));
+                    for (key, key_span, value) in entries(overrides, &package_span) {
+                        let value_span = value.span().unwrap_or(key_span);
+                        match value.as_bool() {
+                            Some(b) => self.line(&value_span, &format!("{key} = {b},")),
+                            None => {
+                                let value = string(value, &value_span)?;
+                                self.line(&value_span, &format!("{key} = {},", lit(value)));
+                            }
+                        }
+                    }
+                    self.line(&package_span, "},");
+                }
+            }
+        }
+        self.line(span, "};");
+        Ok(())
+    }
+
+    fn gitconfig(
+        &mut self,
+        table: &dyn TableLike,
+        span: &Range<usize>,
+        prefix: &str,
+    ) -> Result<(), TomlError> {
+        for (key, key_span, value) in entries(table, span) {
+            let value_span = value.span().unwrap_or(key_span);
+            let key = format!("{prefix}{key}");
+
+            if let Some(nested) = value.as_table_like() {
+                self.gitconfig(nested, &value_span, &format!("{key}."))?;
+            } else if let Some(value) = value.as_str() {
+                self.line(&value_span, &format!("{} = {},", lit(&key), lit(value)));
+            } else {
+                // arrays set multi-valued keys such as `include.path`
+                for (value, span) in strings(value, &value_span)? {
+                    self.line(&span, &format!("{} = {},", lit(&key), lit(value)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn ssh(&mut self, table: &dyn TableLike, span: &Range<usize>) -> Result<(), TomlError> {
+        only(table, span, "ssh", &["keys", "hosts"])?;
+        self.line(span, "ssh {");
+
+        if let Some(keys) = table.get("keys") {
+            for (key, key_span) in tables(keys, span, "ssh.keys")? {
+                only(key, &key_span, "ssh.keys", &["path", "type", "comment"])?;
+                let (path, path_span) = field(key, &key_span, "path")?;
+                self.line(&path_span, &format!("key {}", lit(path)));
+                if key.get("type").is_some() || key.get("comment").is_some() {
+                    self.line(&key_span, "(");
+                    if let Some(key_type) = key.get("type") {
+                        let type_span = key_type.span().unwrap_or_else(|| key_span.clone());
+                        let key_type = keyword(key_type, &key_span, &["ed25519", "ecdsa", "rsa"])?;
+                        self.line(&type_span, &format!("type = {key_type},"));
+                    }
+                    if key.get("comment").is_some() {
+                        let (comment, comment_span) = field(key, &key_span, "comment")?;
+                        self.line(&comment_span, &format!("comment = {},", lit(comment)));
+                    }
+                    self.line(&key_span, ")");
+                }
+                self.line(&key_span, ",");
+            }
+        }
+
+        if let Some(hosts) = table.get("hosts") {
+            for (host, host_span, options) in entries(table_like(hosts, span, "ssh.hosts")?, span) {
+                let host_span = options.span().unwrap_or(host_span);
+                self.line(&host_span, &format!("host {} {{", lit(host)));
+                for (option, option_span, value) in
+                    entries(table_like(options, &host_span, host)?, &host_span)
+                {
+                    if !is_ident(option) {
+                        return Err((option_span, format!("`{option}` isn't a valid ssh option")));
+                    }
+                    let value_span = value.span().unwrap_or(option_span);
+                    let value = match value.as_integer() {
+                        Some(i) => i.to_string(),
+                        None => lit(string(value, &value_span)?),
+                    };
+                    self.line(&value_span, &format!("{option} = {value},"));
+                }
+                self.line(&host_span, "},");
+            }
+        }
+
+        self.line(span, "};");
+        Ok(())
+    }
+}
+
+/// Renders `s` as a Rust string literal
+fn lit(s: &str) -> String {
+    format!("{s:?}")
+}
+
+fn lits(list: &[(&str, Range<usize>)]) -> String {
+    list.iter()
+        .map(|(s, _)| lit(s))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The entries of `table` in order, with the span of each key
+fn entries<'a>(
+    table: &'a dyn TableLike,
+    span: &Range<usize>,
+) -> Vec<(&'a str, Range<usize>, &'a Item)> {
+    table
+        .iter()
+        .map(|(key, item)| {
+            let key_span = table
+                .get_key_value(key)
+                .and_then(|(key, _)| key.span())
+                .unwrap_or_else(|| span.clone());
+            (key, key_span, item)
+        })
+        .collect()
+}
+
+/// Returns an error for the first key of `table` that isn't in `allowed`
+fn only(
+    table: &dyn TableLike,
+    span: &Range<usize>,
+    name: &str,
+    allowed: &[&str],
+) -> Result<(), TomlError> {
+    match entries(table, span)
+        .into_iter()
+        .find(|(key, _, _)| !allowed.contains(key))
+    {
+        Some((key, key_span, _)) => Err((key_span, format!("unknown key `{key}` in `{name}`"))),
+        None => Ok(()),
+    }
+}
+
+fn required<'a>(
+    table: &'a dyn TableLike,
+    span: &Range<usize>,
+    key: &str,
+) -> Result<&'a Item, TomlError> {
+    table
+        .get(key)
+        .ok_or_else(|| (span.clone(), format!("missing `{key}`")))
+}
+
+/// The string at `key` in `table`, along with its span
+fn field<'a>(
+    table: &'a dyn TableLike,
+    span: &Range<usize>,
+    key: &str,
+) -> Result<(&'a str, Range<usize>), TomlError> {
+    let item = required(table, span, key)?;
+    let item_span = item.span().unwrap_or_else(|| span.clone());
+    Ok((string(item, &item_span)?, item_span))
+}
+
+/// A string that's written as a keyword in the installer syntax, so it has to be one of `allowed`
+fn keyword<'a>(item: &'a Item, span: &Range<usize>, allowed: &[&str]) -> Result<&'a str, TomlError> {
+    let value = string(item, span)?;
+    match allowed.contains(&value) {
+        true => Ok(value),
+        false => Err((
+            item.span().unwrap_or_else(|| span.clone()),
+            format!("expected one of `{}`, found `{value}`", allowed.join("`, `")),
+        )),
+    }
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn table_like<'a>(
+    item: &'a Item,
+    span: &Range<usize>,
+    name: &str,
+) -> Result<&'a dyn TableLike, TomlError> {
+    item.as_table_like()
+        .ok_or_else(|| (span.clone(), format!("expected `{name}` to be a table")))
+}
+
+/// An array of tables, written either as `[[name]]` or as an array of inline tables
+fn tables<'a>(
+    item: &'a Item,
+    span: &Range<usize>,
+    name: &str,
+) -> Result<Vec<SpannedTable<'a>>, TomlError> {
+    let error = || {
+        (
+            span.clone(),
+            format!("expected `{name}` to be an array of tables"),
+        )
+    };
+
+    match item {
+        Item::ArrayOfTables(array) => Ok(array
+            .iter()
+            .map(|t| {
+                (
+                    t as &dyn TableLike,
+                    t.span().unwrap_or_else(|| span.clone()),
+                )
+            })
+            .collect()),
+        Item::Value(Value::Array(array)) => array
+            .iter()
+            .map(|v| match v {
+                Value::InlineTable(t) => Ok((
+                    t as &dyn TableLike,
+                    t.span().unwrap_or_else(|| span.clone()),
+                )),
+                _ => Err(error()),
+            })
+            .collect(),
+        _ => Err(error()),
+    }
+}
+
+fn string<'a>(item: &'a Item, span: &Range<usize>) -> Result<&'a str, TomlError> {
+    item.as_str().ok_or_else(|| {
+        (
+            item.span().unwrap_or_else(|| span.clone()),
+            "expected a string".to_string(),
+        )
+    })
+}
+
+fn integer(item: &Item, span: &Range<usize>) -> Result<i64, TomlError> {
+    item.as_integer().ok_or_else(|| {
+        (
+            item.span().unwrap_or_else(|| span.clone()),
+            "expected an integer".to_string(),
+        )
+    })
+}
+
+/// An array of strings, with the span of each string
+fn strings<'a>(
+    item: &'a Item,
+    span: &Range<usize>,
+) -> Result<Vec<(&'a str, Range<usize>)>, TomlError> {
+    let array = item.as_array().ok_or_else(|| {
+        (
+            item.span().unwrap_or_else(|| span.clone()),
+            "expected an array of strings".to_string(),
+        )
+    })?;
+
+    array
+        .iter()
+        .map(|v| {
+            let v_span = v.span().unwrap_or_else(|| span.clone());
+            v.as_str()
+                .map(|s| (s, v_span.clone()))
+                .ok_or((v_span, "expected a string".to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+    use crate::installer::parse::Section;
+
+    fn location(contents: &str) -> (usize, usize, String) {
+        let (span, message) = parse_toml(contents).err().unwrap().remove(0);
+        let (line, column) = line_column(contents, span.start);
+        (line, column, message)
+    }
+
+    #[test]
+    fn parses_toml_installer() {
+        let installer = parse_toml(
+            r#"
+[cargo]
+crates = ["ripgrep", "exa"]
+
+[ensure]
+paths = ["~/.local/bin", "/foo/bar"]
+
+[exec]
+scripts = ["./install_fonts.sh"]
+
+[packages]
+unzip = true
+nerd-fonts = { optional = true }
+
+[packages.build-essential]
+pacman = "base-devel"
+
+[[symlinks]]
+link = "~/.bashrc"
+original = "config/bashrc"
+
+[pipx]
+black = "23.1.0"
+httpie = "*"
+
+[gitconfig.user]
+name = "Jane Doe"
+
+[[cron]]
+schedule = "0 * * * *"
+command = "~/bin/sync-notes"
+
+[secrets]
+files = [{ source = "secrets/netrc.age", destination = "~/.netrc", mode = 0o600 }]
+
+[ssh.hosts.build]
+HostName = "10.0.0.5"
+Port = 2222
+"#,
+        )
+        .ok()
+        .unwrap();
+
+        let sections = &installer.sections;
+        assert_eq!(sections.len(), 10);
+
+        assert!(matches!(&sections[0], Section::Cargo(c) if c.crates[1].value() == "exa"));
+        assert!(
+            matches!(&sections[1], Section::Ensure(e) if e.paths[0].value() == "~/.local/bin")
+        );
+        assert!(
+            matches!(&sections[2], Section::Script(s) if s.path.value() == "./install_fonts.sh")
+        );
+
+        let packages = match &sections[3] {
+            Section::Packages(p) => &p.packages,
+            _ => panic!("expected packages"),
+        };
+        assert_eq!(packages[0].name.value(), "unzip");
+        assert!(packages[1].optional);
+        assert_eq!(packages[2].name.value(), "build-essential");
+        assert_eq!(packages[2].pacman.as_ref().unwrap().value(), "base-devel");
+
+        assert!(
+            matches!(&sections[4], Section::Symlinks(s) if s.links[0].original.value() == "config/bashrc")
+        );
+        assert!(matches!(&sections[5], Section::Pipx(p) if p.tools[1].version.is_none()));
+        assert!(
+            matches!(&sections[6], Section::GitConfig(g) if g.entries[0].key.value() == "user.name")
+        );
+        assert!(matches!(&sections[7], Section::Cron(_)));
+        assert!(matches!(&sections[8], Section::Secrets(s) if s.secrets[0].mode == 0o600));
+        assert!(
+            matches!(&sections[9], Section::Ssh(s) if s.hosts[0].options[1].1.value() == "2222")
+        );
+    }
+
+    #[test]
+    fn reports_toml_locations() {
+        assert_eq!(
+            location("[cargo]\ncrates = [\"ripgrep\", 42]\n"),
+            (2, 22, "expected a string".to_string())
+        );
+        assert_eq!(
+            location("[cargo]\ncrates = []\n\n[homebrew]\n"),
+            (4, 2, "unknown section `homebrew`".to_string())
+        );
+        assert_eq!(
+            location("[packages.foo]\nzypper = \"foo\"\n"),
+            (2, 1, "unknown key `zypper` in `foo`".to_string())
+        );
+
+        // errors from validating the sections point at the value that caused them
+        let (line, column, message) =
+            location("[[cron]]\nschedule = \"61 * * * *\"\ncommand = \"foo\"\n");
+        assert_eq!((line, column), (2, 12));
+        assert!(message.starts_with("invalid cron schedule"), "{message}");
+
+        let (line, column, _) = location("[[cron]]\nschedule = \"@daily\"\ncommand = \"date +%F\"\n");
+        assert_eq!((line, column), (3, 11));
+
+        let (line, _, _) = location("[cargo]\ncrates = [\n  \"ripgrep\",\n]\n[pipx]\nblack =");
+        assert_eq!(line, 6);
+    }
+
+    #[test]
+    fn rejects_values_that_arent_keywords() {
+        let (line, column, message) = location(
+            "[[services]]\nname = \"a\"\nstate = \"enabled }; cargo { \\\"evil\\\"\"\n",
+        );
+        assert_eq!((line, column), (3, 9));
+        assert!(message.starts_with("expected one of `enabled`, `started`"), "{message}");
+
+        let (_, _, message) =
+            location("[[services]]\nname = \"a\"\nscope = \"user \\\"b\\\" =>\"\nstate = \"enabled\"\n");
+        assert!(message.starts_with("expected one of `user`, `system`"), "{message}");
+
+        let (_, _, message) = location("[[ssh.keys]]\npath = \"~/.ssh/id\"\ntype = \"rsa) }; exec \\\"x\\\"; ssh { key \\\"y\\\" (\"\n");
+        assert!(message.starts_with("expected one of `ed25519`"), "{message}");
+
+        let (_, _, message) = location("[ssh.hosts.build]\n\"Port = 1 }; cron\" = \"x\"\n");
+        assert!(message.contains("isn't a valid ssh option"), "{message}");
+    }
+
+    #[test]
+    fn parses_macro_input() {
+        let input: TomlInstaller = parse_str(r#"pub(crate) struct Laptop; "laptop.toml""#).unwrap();
+        assert_eq!(input.header.name, "Laptop");
+        assert_eq!(input.path.value(), "laptop.toml");
+
+        let input: TomlInstaller = parse_str(r#""dotfiles.toml""#).unwrap();
+        assert_eq!(input.header.name, "Installer");
+    }
+}
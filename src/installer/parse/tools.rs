@@ -0,0 +1,151 @@
+use syn::{braced, parse::Parse, punctuated::Punctuated, Error, LitStr, Token};
+
+use super::kw;
+
+/// A tool from a language ecosystem, optionally pinned to a version with `"name" => "version"`
+pub struct Tool {
+    pub name: LitStr,
+    pub version: Option<LitStr>,
+}
+
+impl Parse for Tool {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let arrow: Option<Token![=>]> = input.parse()?;
+        let version = match arrow {
+            Some(_) => Some(input.parse()?),
+            None => None,
+        };
+
+        Ok(Self { name, version })
+    }
+}
+
+fn parse_tools(input: syn::parse::ParseStream) -> syn::Result<Vec<Tool>> {
+    let content;
+    let _ = braced!(content in input);
+
+    let tools = Punctuated::<Tool, Token![,]>::parse_terminated(&content)?;
+    Ok(tools.into_iter().collect())
+}
+
+pub struct Pipx {
+    pub pipx_kw: kw::pipx,
+    pub tools: Vec<Tool>,
+}
+
+impl Parse for Pipx {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let pipx_kw = input.parse()?;
+        let tools = parse_tools(input)?;
+        Ok(Self { pipx_kw, tools })
+    }
+}
+
+pub struct Npm {
+    pub npm_kw: kw::npm,
+    pub tools: Vec<Tool>,
+}
+
+impl Parse for Npm {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let npm_kw = input.parse()?;
+        let tools = parse_tools(input)?;
+        Ok(Self { npm_kw, tools })
+    }
+}
+
+pub struct Go {
+    pub go_kw: kw::go,
+    pub tools: Vec<Tool>,
+}
+
+impl Parse for Go {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let go_kw = input.parse()?;
+        let mut tools = parse_tools(input)?;
+
+        // `go install` takes the version as part of the package path, so `"path@version"` is accepted as well
+        for tool in &mut tools {
+            let name = tool.name.value();
+            if let Some((path, version)) = name.split_once('@') {
+                if tool.version.is_some() {
+                    return Err(Error::new(
+                        tool.name.span(),
+                        format!("version of `{path}` is specified twice"),
+                    ));
+                }
+                tool.version = Some(LitStr::new(version, tool.name.span()));
+                tool.name = LitStr::new(path, tool.name.span());
+            }
+        }
+
+        Ok(Self { go_kw, tools })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_str;
+
+    use super::*;
+
+    fn names_and_versions(tools: &[Tool]) -> Vec<(String, Option<String>)> {
+        tools
+            .iter()
+            .map(|t| (t.name.value(), t.version.as_ref().map(|v| v.value())))
+            .collect()
+    }
+
+    #[test]
+    fn parses_pipx_section() {
+        let pipx: Pipx = parse_str(r#"pipx { "black" => "23.1.0", "httpie" }"#).unwrap();
+        assert_eq!(
+            names_and_versions(&pipx.tools),
+            [
+                ("black".to_string(), Some("23.1.0".to_string())),
+                ("httpie".to_string(), None)
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_npm_section() {
+        let npm: Npm = parse_str(r#"npm { "prettier", "@angular/cli" => "17" }"#).unwrap();
+        assert_eq!(
+            names_and_versions(&npm.tools),
+            [
+                ("prettier".to_string(), None),
+                ("@angular/cli".to_string(), Some("17".to_string()))
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_go_section() {
+        let go: Go = parse_str(
+            r#"go {
+                "golang.org/x/tools/gopls@latest",
+                "github.com/go-delve/delve/cmd/dlv" => "v1.22.0",
+                "mvdan.cc/gofumpt",
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            names_and_versions(&go.tools),
+            [
+                (
+                    "golang.org/x/tools/gopls".to_string(),
+                    Some("latest".to_string())
+                ),
+                (
+                    "github.com/go-delve/delve/cmd/dlv".to_string(),
+                    Some("v1.22.0".to_string())
+                ),
+                ("mvdan.cc/gofumpt".to_string(), None),
+            ]
+        );
+
+        assert!(parse_str::<Go>(r#"go { "golang.org/x/tools/gopls@latest" => "v0.14.0" }"#).is_err());
+    }
+}
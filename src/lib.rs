@@ -6,3 +6,37 @@ mod installer;
 pub fn installer(tokens: TokenStream) -> TokenStream {
     installer::installer(tokens.into()).into()
 }
+
+#[proc_macro]
+pub fn installer_from_toml(tokens: TokenStream) -> TokenStream {
+    installer::installer_from_toml(tokens.into()).into()
+}
+
+#[proc_macro_derive(
+    Installer,
+    attributes(
+        nested,
+        aliases,
+        cargo,
+        cron,
+        ensure,
+        exec,
+        extract,
+        fonts,
+        functions,
+        gitconfig,
+        go,
+        npm,
+        packages,
+        pipx,
+        repositories,
+        rustup,
+        secrets,
+        services,
+        ssh,
+        symlinks
+    )
+)]
+pub fn derive_installer(tokens: TokenStream) -> TokenStream {
+    installer::derive_installer(tokens.into()).into()
+}